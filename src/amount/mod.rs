@@ -0,0 +1,201 @@
+#![allow(unused)]
+use std::fmt::Display;
+
+/// Satoshis in one bitcoin
+pub const SATS_PER_BTC: u64 = 100_000_000;
+
+/// Most bitcoin that will ever exist, in satoshis
+///
+/// The real supply ends up slightly below this because of rounding in the subsidy, but
+/// consensus code checks amounts against this constant.
+pub const MAX_MONEY: u64 = 21_000_000 * SATS_PER_BTC;
+
+/// Reasons a BTC amount can fail to parse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AmountError {
+    /// The string is empty or has something other than digits and a single `.`
+    InvalidFormat,
+    /// More than 8 decimal places, which would be a fraction of a satoshi
+    TooPrecise,
+    /// Above `MAX_MONEY`
+    TooLarge,
+}
+
+impl Display for AmountError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidFormat => write!(f, "Invalid BTC amount"),
+            Self::TooPrecise => write!(f, "BTC amount has more than 8 decimal places"),
+            Self::TooLarge => write!(f, "BTC amount is larger than the total supply"),
+        }
+    }
+}
+
+impl std::error::Error for AmountError {}
+
+/// An amount of bitcoin, stored in satoshis
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Amount(u64);
+
+impl Amount {
+    pub const ZERO: Self = Self(0);
+
+    pub fn from_sat(satoshis: u64) -> Self {
+        Self(satoshis)
+    }
+
+    pub fn to_sat(self) -> u64 {
+        self.0
+    }
+
+    /// Formats the amount in BTC, like `0.00000001` or `21000000`
+    ///
+    /// The fraction is written with up to 8 decimal places, without trailing zeros.
+    pub fn to_btc_string(&self) -> String {
+        let whole = self.0 / SATS_PER_BTC;
+        let fraction = self.0 % SATS_PER_BTC;
+        if fraction == 0 {
+            return whole.to_string();
+        }
+
+        let fraction = format!("{fraction:08}");
+        format!("{whole}.{}", fraction.trim_end_matches('0'))
+    }
+
+    /// Parses a decimal BTC amount, like `0.5` or `21000000`
+    ///
+    /// The digits are read as an integer number of satoshis, so there is no floating
+    /// point rounding involved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string is not a plain decimal number, has more than 8
+    /// decimal places, or is above `MAX_MONEY`
+    pub fn from_btc_str(s: &str) -> Result<Self, AmountError> {
+        let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+        let is_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+        if whole.is_empty()
+            || !is_digits(whole)
+            || !is_digits(fraction)
+            || (s.contains('.') && fraction.is_empty())
+        {
+            return Err(AmountError::InvalidFormat);
+        }
+        if fraction.len() > 8 {
+            return Err(AmountError::TooPrecise);
+        }
+
+        // Anything that doesn't fit in a u64 is far above the supply anyway
+        let whole: u64 = whole.parse().map_err(|_| AmountError::TooLarge)?;
+        let fraction: u64 = format!("{fraction:0<8}")
+            .parse()
+            .map_err(|_| AmountError::InvalidFormat)?;
+
+        let satoshis = whole
+            .checked_mul(SATS_PER_BTC)
+            .and_then(|satoshis| satoshis.checked_add(fraction))
+            .filter(|&satoshis| satoshis <= MAX_MONEY)
+            .ok_or(AmountError::TooLarge)?;
+
+        Ok(Self(satoshis))
+    }
+}
+
+impl Display for Amount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} BTC", self.to_btc_string())
+    }
+}
+
+/// Returns what a UTXO is worth once the fee to spend it is paid
+///
+/// The fee is `input_vsize * fee_rate`, with the fee rate in satoshis per virtual byte.
+/// Coin selection ranks UTXOs by this rather than their amount, and dust UTXOs at high
+/// fee rates cost more to spend than they bring in, so the result can be negative.
+pub fn effective_value(amount: u64, input_vsize: usize, fee_rate: u64) -> i64 {
+    let fee = (input_vsize as u64).saturating_mul(fee_rate);
+    let value = i128::from(amount) - i128::from(fee);
+    i64::try_from(value).unwrap_or(if value < 0 { i64::MIN } else { i64::MAX })
+}
+
+/// Serializes a satoshi amount the way transaction outputs carry it, 8 little endian bytes
+pub fn to_le_bytes(sats: u64) -> [u8; 8] {
+    sats.to_le_bytes()
+}
+
+/// Reads a satoshi amount from its 8 little endian wire bytes
+pub fn from_le_bytes(bytes: [u8; 8]) -> u64 {
+    u64::from_le_bytes(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_btc_string() {
+        assert_eq!(Amount::from_sat(1).to_btc_string(), "0.00000001");
+        assert_eq!(Amount::from_sat(MAX_MONEY).to_btc_string(), "21000000");
+        assert_eq!(Amount::from_sat(150_000_000).to_btc_string(), "1.5");
+        assert_eq!(Amount::from_sat(1_234_567_890).to_btc_string(), "12.3456789");
+        assert_eq!(Amount::ZERO.to_btc_string(), "0");
+        assert_eq!(Amount::from_sat(u64::MAX).to_btc_string(), "184467440737.09551615");
+        assert_eq!(format!("{}", Amount::from_sat(50_000)), "0.0005 BTC");
+    }
+
+    #[test]
+    fn test_from_btc_str() {
+        assert_eq!(Amount::from_btc_str("0.00000001"), Ok(Amount::from_sat(1)));
+        assert_eq!(Amount::from_btc_str("21000000"), Ok(Amount::from_sat(MAX_MONEY)));
+        assert_eq!(Amount::from_btc_str("1.5"), Ok(Amount::from_sat(150_000_000)));
+        assert_eq!(Amount::from_btc_str("0.10000000"), Ok(Amount::from_sat(10_000_000)));
+        assert_eq!(Amount::from_btc_str("0"), Ok(Amount::ZERO));
+
+        // 0.1 + 0.2 isn't 0.3 in floating point, but is here
+        let sum = Amount::from_btc_str("0.1").unwrap().to_sat()
+            + Amount::from_btc_str("0.2").unwrap().to_sat();
+        assert_eq!(Amount::from_btc_str("0.3"), Ok(Amount::from_sat(sum)));
+
+        for sats in [1, 546, 10_000_000, 123_456_789, MAX_MONEY] {
+            let amount = Amount::from_sat(sats);
+            assert_eq!(Amount::from_btc_str(&amount.to_btc_string()), Ok(amount));
+        }
+    }
+
+    #[test]
+    fn test_from_btc_str_errors() {
+        assert_eq!(Amount::from_btc_str("0.000000001"), Err(AmountError::TooPrecise));
+        assert_eq!(Amount::from_btc_str("21000000.00000001"), Err(AmountError::TooLarge));
+        assert_eq!(Amount::from_btc_str("99999999999999999999"), Err(AmountError::TooLarge));
+
+        for invalid in ["", ".", "1.", ".5", "-1", "+1", "1.2.3", "1 BTC", " 1", "1e8", "0x10"] {
+            assert_eq!(Amount::from_btc_str(invalid), Err(AmountError::InvalidFormat), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_effective_value() {
+        // A P2WPKH input is 68 vbytes
+        assert_eq!(effective_value(100_000, 68, 10), 99_320);
+        assert_eq!(effective_value(100_000, 68, 0), 100_000);
+
+        // Dust at a high fee rate is worth less than nothing
+        assert_eq!(effective_value(546, 68, 50), -2_854);
+        assert!(effective_value(Amount::ZERO.to_sat(), 148, 1) < 0);
+
+        assert_eq!(effective_value(u64::MAX, 0, 1), i64::MAX);
+        assert_eq!(effective_value(0, usize::MAX, u64::MAX), i64::MIN);
+    }
+
+    #[test]
+    fn test_le_bytes() {
+        // 50 BTC, the first block subsidy
+        let bytes = to_le_bytes(50 * SATS_PER_BTC);
+        assert_eq!(bytes, [0x00, 0xf2, 0x05, 0x2a, 0x01, 0x00, 0x00, 0x00]);
+        assert_eq!(from_le_bytes(bytes), 50 * SATS_PER_BTC);
+
+        for sats in [0, 1, 546, MAX_MONEY, u64::MAX] {
+            assert_eq!(from_le_bytes(to_le_bytes(sats)), sats);
+        }
+    }
+}
@@ -0,0 +1,549 @@
+#![allow(unused)]
+use crate::{
+    elliptic_curve::public_key::Network,
+    helpers::{bytes::ToBytesBigEndian, hash::hash256_array, reader::Reader},
+    merkle::{merkle_parent, merkle_root},
+};
+use color_eyre::eyre::Result;
+use primitive_types::U256;
+use std::fmt::Display;
+
+/// Number of blocks between subsidy halvings
+pub const HALVING_INTERVAL: u32 = 210_000;
+
+/// Highest target mainnet and testnet accept, `0x00000000ffff..ff`
+pub const POW_LIMIT: U256 = U256([u64::MAX, u64::MAX, u64::MAX, 0x0000_0000_ffff_ffff]);
+
+/// Most transactions a block can hold, its weight limit over the smallest transaction
+pub const MAX_BLOCK_TRANSACTIONS: u32 = 4_000_000 / 240;
+
+/// Reasons a `merkleblock` message can fail to parse or verify
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockError {
+    /// The message ends early or has bytes left over
+    InvalidLength,
+    /// Zero transactions, more than a block can hold or fewer than there are hashes
+    InvalidTransactionCount(u32),
+    /// The flags and hashes don't describe exactly one partial merkle tree
+    InvalidPartialTree,
+    /// The tree doesn't hash to the header's merkle root
+    MerkleRootMismatch,
+}
+
+impl Display for BlockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidLength => write!(f, "Merkle block message has the wrong length"),
+            Self::InvalidTransactionCount(count) => {
+                write!(f, "Invalid merkle block transaction count {count}")
+            }
+            Self::InvalidPartialTree => write!(f, "Invalid partial merkle tree"),
+            Self::MerkleRootMismatch => write!(f, "Partial merkle tree doesn't match the header"),
+        }
+    }
+}
+
+impl std::error::Error for BlockError {}
+
+/// Returns the block subsidy in satoshis at the given height
+///
+/// Starts at 50 BTC and halves every `HALVING_INTERVAL` blocks. Shifting by 64 or more
+/// is undefined for a `u64`, so the subsidy is zero from the 64th halving on.
+pub fn subsidy(height: u32) -> u64 {
+    let halvings = height / HALVING_INTERVAL;
+    if halvings >= 64 {
+        return 0;
+    }
+
+    (50 * 100_000_000) >> halvings
+}
+
+/// Expands the compact "bits" encoding of a block's target
+///
+/// The top byte is the length of the target in bytes and the lower three bytes are its
+/// most significant bytes. Bit 23 is a sign bit, negative targets are treated as zero.
+pub fn target_from_bits(bits: u32) -> U256 {
+    let exponent = bits >> 24;
+    let mantissa = bits & 0x007f_ffff;
+    if bits & 0x0080_0000 != 0 {
+        return U256::zero();
+    }
+
+    if exponent <= 3 {
+        U256::from(mantissa >> (8 * (3 - exponent)))
+    } else {
+        U256::from(mantissa) << (8 * (exponent - 3))
+    }
+}
+
+/// Expands the bits the way Bitcoin Core's `SetCompact` does, rejecting what it flags
+///
+/// Returns `None` when a nonzero mantissa has the sign bit set or doesn't fit in 256 bits
+/// once shifted by the exponent. A zero mantissa is zero whatever the other bits are.
+pub fn checked_target_from_bits(bits: u32) -> Option<U256> {
+    let exponent = bits >> 24;
+    let mantissa = bits & 0x007f_ffff;
+    let negative = bits & 0x0080_0000 != 0;
+    let overflow = exponent > 34
+        || (mantissa > 0xff && exponent > 33)
+        || (mantissa > 0xffff && exponent > 32);
+
+    if mantissa != 0 && (negative || overflow) {
+        return None;
+    }
+    Some(target_from_bits(bits & !0x0080_0000))
+}
+
+/// Packs a target into the compact "bits" encoding
+///
+/// Like Bitcoin Core, the mantissa is shifted one more byte when its top bit is set, so
+/// the result never reads back as negative. Precision below the top three bytes is lost.
+pub fn bits_from_target(target: U256) -> u32 {
+    let mut size = target.bits().div_ceil(8);
+    let mut compact = if size <= 3 {
+        target.low_u64() << (8 * (3 - size))
+    } else {
+        (target >> (8 * (size - 3))).low_u64()
+    };
+
+    if compact & 0x0080_0000 != 0 {
+        compact >>= 8;
+        size += 1;
+    }
+
+    // compact is at most 3 bytes and size is at most 33
+    #[allow(clippy::cast_possible_truncation)]
+    let bits = compact as u32 | (size as u32) << 24;
+    bits
+}
+
+/// Returns the target of the bits as 64 hex characters, the way block explorers show it
+pub fn target_hex(bits: u32) -> String {
+    format!("{:064x}", target_from_bits(bits))
+}
+
+/// The genesis block's only transaction, with the Times headline in its coinbase
+pub const GENESIS_COINBASE: &str = "01000000010000000000000000000000000000000000000000000000\
+    000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039\
+    204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f7220\
+    62616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e039\
+    09a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac\
+    00000000";
+
+/// An 80 byte block header
+///
+/// Hashes are in internal byte order, reversed from how they are usually displayed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub version: i32,
+    pub prev_block: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub time: u32,
+    pub bits: u32,
+    pub nonce: u32,
+}
+
+impl BlockHeader {
+    pub fn serialize(&self) -> [u8; 80] {
+        let mut serialized = [0u8; 80];
+        serialized[..4].copy_from_slice(&self.version.to_le_bytes());
+        serialized[4..36].copy_from_slice(&self.prev_block);
+        serialized[36..68].copy_from_slice(&self.merkle_root);
+        serialized[68..72].copy_from_slice(&self.time.to_le_bytes());
+        serialized[72..76].copy_from_slice(&self.bits.to_le_bytes());
+        serialized[76..].copy_from_slice(&self.nonce.to_le_bytes());
+        serialized
+    }
+
+    /// Reads a header
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input ends early
+    pub fn parse(reader: &mut Reader<'_>) -> Result<Self> {
+        Ok(Self {
+            version: i32::from_le_bytes(reader.read_array()?),
+            prev_block: reader.read_array()?,
+            merkle_root: reader.read_array()?,
+            time: reader.read_u32_le()?,
+            bits: reader.read_u32_le()?,
+            nonce: reader.read_u32_le()?,
+        })
+    }
+
+    /// Returns the block hash, `hash256` of the serialized header
+    pub fn hash(&self) -> [u8; 32] {
+        hash256_array(&self.serialize())
+    }
+
+    pub fn target(&self) -> U256 {
+        target_from_bits(self.bits)
+    }
+
+    /// Returns the target as 64 hex characters, see `target_hex`
+    pub fn target_hex(&self) -> String {
+        target_hex(self.bits)
+    }
+
+    /// Returns whether the hash, read as a little endian number, is at most the target
+    ///
+    /// Like Bitcoin Core's `CheckProofOfWork`, bits that are negative, overflow, expand
+    /// to zero or to a target above `POW_LIMIT` fail whatever the hash is.
+    pub fn check_pow(&self) -> bool {
+        match checked_target_from_bits(self.bits) {
+            Some(target) if !target.is_zero() && target <= POW_LIMIT => {
+                U256::from_little_endian(&self.hash()) <= target
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns whether the transaction ids hash up to the header's merkle root
+    pub fn validate_merkle_root(&self, txids: &[[u8; 32]]) -> bool {
+        merkle_root(txids) == self.merkle_root
+    }
+}
+
+/// Returns the header of the network's genesis block
+///
+/// Both networks share the same coinbase, and so the same merkle root, and differ only
+/// in time and nonce.
+///
+/// # Panics
+///
+/// Doesn't panic in practice, the hardcoded coinbase is valid hex
+pub fn genesis(network: Network) -> BlockHeader {
+    let coinbase_txid = hash256_array(&GENESIS_COINBASE.to_bytes_be());
+    let (time, nonce) = match network {
+        Network::Mainnet => (1_231_006_505, 2_083_236_893),
+        Network::Testnet => (1_296_688_602, 414_098_458),
+    };
+
+    BlockHeader {
+        version: 1,
+        prev_block: [0u8; 32],
+        merkle_root: merkle_root(&[coinbase_txid]),
+        time,
+        bits: 0x1d00_ffff,
+        nonce,
+    }
+}
+
+/// Walks a BIP37 partial merkle tree depth first, consuming flags and hashes
+struct PartialMerkleTree<'a> {
+    transactions: u32,
+    hashes: &'a [[u8; 32]],
+    flags: &'a [u8],
+    hashes_used: usize,
+    flags_used: usize,
+    matched: Vec<[u8; 32]>,
+}
+
+impl PartialMerkleTree<'_> {
+    /// Number of nodes at the height, counting up from the transactions at height 0
+    fn width(&self, height: u32) -> u32 {
+        (self.transactions - 1 + (1 << height)) >> height
+    }
+
+    fn next_flag(&mut self) -> Result<bool, BlockError> {
+        let byte = self.flags.get(self.flags_used / 8).ok_or(BlockError::InvalidPartialTree)?;
+        let flag = byte >> (self.flags_used % 8) & 1 == 1;
+        self.flags_used += 1;
+        Ok(flag)
+    }
+
+    fn next_hash(&mut self) -> Result<[u8; 32], BlockError> {
+        let hash = *self.hashes.get(self.hashes_used).ok_or(BlockError::InvalidPartialTree)?;
+        self.hashes_used += 1;
+        Ok(hash)
+    }
+
+    /// Returns the hash of the node at the height and position
+    ///
+    /// A clear flag means the subtree has no matches and its hash is given as is. A set
+    /// flag on a leaf marks a matched transaction, on an inner node it means descend.
+    fn traverse(&mut self, height: u32, position: u32) -> Result<[u8; 32], BlockError> {
+        let flag = self.next_flag()?;
+        if height == 0 || !flag {
+            let hash = self.next_hash()?;
+            if height == 0 && flag {
+                self.matched.push(hash);
+            }
+            return Ok(hash);
+        }
+
+        let left = self.traverse(height - 1, position * 2)?;
+        if position * 2 + 1 >= self.width(height - 1) {
+            return Ok(merkle_parent(&left, &left));
+        }
+
+        // Identical siblings would let a different transaction list hash to the same root
+        let right = self.traverse(height - 1, position * 2 + 1)?;
+        if right == left {
+            return Err(BlockError::InvalidPartialTree);
+        }
+        Ok(merkle_parent(&left, &right))
+    }
+}
+
+/// Parses a BIP37 `merkleblock` message and returns the header and matched txids
+///
+/// The message is the header, the block's transaction count, the hashes and the flag
+/// bits of a partial merkle tree, which is rebuilt to check that the matched txids
+/// hash up to the header's merkle root. Like Bitcoin Core, every hash and every flag
+/// byte has to be used. Txids are in internal byte order.
+///
+/// # Errors
+///
+/// Returns an error if the message is cut short or has trailing bytes, the transaction
+/// count is invalid, the flags and hashes don't form a tree, or the tree's root is not
+/// the header's merkle root
+pub fn parse_merkle_block(bytes: &[u8]) -> Result<(BlockHeader, Vec<[u8; 32]>), BlockError> {
+    let mut reader = Reader::new(bytes);
+    let header = BlockHeader::parse(&mut reader).map_err(|_| BlockError::InvalidLength)?;
+    let transactions = reader.read_u32_le().map_err(|_| BlockError::InvalidLength)?;
+    let hash_count = reader.read_varint().map_err(|_| BlockError::InvalidLength)?;
+    if transactions == 0
+        || transactions > MAX_BLOCK_TRANSACTIONS
+        || hash_count > u64::from(transactions)
+    {
+        return Err(BlockError::InvalidTransactionCount(transactions));
+    }
+
+    let hashes = (0..hash_count)
+        .map(|_| reader.read_array())
+        .collect::<Result<Vec<[u8; 32]>>>()
+        .map_err(|_| BlockError::InvalidLength)?;
+    let flag_count = reader.read_varint().map_err(|_| BlockError::InvalidLength)?;
+    let flags = usize::try_from(flag_count)
+        .ok()
+        .and_then(|flag_count| reader.read_bytes(flag_count).ok())
+        .ok_or(BlockError::InvalidLength)?;
+    if !reader.is_empty() {
+        return Err(BlockError::InvalidLength);
+    }
+
+    let mut tree = PartialMerkleTree {
+        transactions,
+        hashes: &hashes,
+        flags,
+        hashes_used: 0,
+        flags_used: 0,
+        matched: Vec::new(),
+    };
+    let mut height = 0;
+    while tree.width(height) > 1 {
+        height += 1;
+    }
+    let root = tree.traverse(height, 0)?;
+
+    if tree.hashes_used != hashes.len() || tree.flags_used.div_ceil(8) != flags.len() {
+        return Err(BlockError::InvalidPartialTree);
+    }
+    if root != header.merkle_root {
+        return Err(BlockError::MerkleRootMismatch);
+    }
+    Ok((header, tree.matched))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::hex::{format_hash_display, parse_hash_display};
+
+    #[test]
+    fn test_target_hex() {
+        // Genesis block
+        assert_eq!(
+            target_hex(0x1d00_ffff),
+            "00000000ffff0000000000000000000000000000000000000000000000000000"
+        );
+        assert_eq!(
+            target_hex(0x1703_0ecd),
+            "000000000000000000030ecd0000000000000000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn test_bits_round_trip() {
+        // Genesis block, block 100000 (0x1b04864c) and other normalized values
+        for bits in [0x1d00_ffff, 0x1d00_d86a, 0x1b04_864c, 0x1806_b99f, 0x1705_e0b6] {
+            assert_eq!(bits_from_target(target_from_bits(bits)), bits);
+        }
+
+        // A mantissa with its top bit set is moved into the next byte
+        assert_eq!(bits_from_target(U256::from(0x80u32)), 0x0200_8000);
+        assert_eq!(bits_from_target(U256::zero()), 0);
+        assert_eq!(target_from_bits(0x0392_3456), U256::zero());
+    }
+
+    #[test]
+    fn test_subsidy() {
+        assert_eq!(subsidy(0), 5_000_000_000);
+        assert_eq!(subsidy(209_999), 5_000_000_000);
+        assert_eq!(subsidy(210_000), 2_500_000_000);
+        assert_eq!(subsidy(840_000), 312_500_000);
+
+        // The subsidy is shifted below a satoshi after 33 halvings
+        assert_eq!(subsidy(33 * HALVING_INTERVAL), 0);
+        assert_eq!(subsidy(64 * HALVING_INTERVAL), 0);
+        assert_eq!(subsidy(u32::MAX), 0);
+    }
+
+    #[test]
+    fn test_genesis() {
+        let mainnet = genesis(Network::Mainnet);
+        assert_eq!(
+            format_hash_display(&mainnet.hash()),
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"
+        );
+        assert_eq!(
+            format_hash_display(&mainnet.merkle_root),
+            "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"
+        );
+        assert!(mainnet.check_pow());
+        assert_eq!(
+            mainnet.target_hex(),
+            "00000000ffff0000000000000000000000000000000000000000000000000000"
+        );
+        assert!(mainnet.validate_merkle_root(&[mainnet.merkle_root]));
+        assert!(!mainnet.validate_merkle_root(&[mainnet.merkle_root, mainnet.merkle_root]));
+
+        let testnet = genesis(Network::Testnet);
+        assert_eq!(
+            testnet.hash(),
+            parse_hash_display("000000000933ea01ad0ee984209779baaec3ced90fa3f408719526f8d77f4943")
+                .unwrap()
+        );
+        assert!(testnet.check_pow());
+
+        let serialized = mainnet.serialize();
+        assert_eq!(BlockHeader::parse(&mut Reader::new(&serialized)).unwrap(), mainnet);
+        assert!(BlockHeader::parse(&mut Reader::new(&serialized[..79])).is_err());
+
+        // Any other nonce almost certainly misses the target
+        let mut tampered = mainnet;
+        tampered.nonce += 1;
+        assert!(!tampered.check_pow());
+    }
+
+    #[test]
+    fn test_check_pow_invalid_bits() {
+        assert_eq!(format!("{POW_LIMIT:064x}"), format!("00000000{}", "f".repeat(56)));
+        assert_eq!(checked_target_from_bits(0x1d00_ffff), Some(target_from_bits(0x1d00_ffff)));
+        // A zero mantissa is zero even with the sign bit or a huge exponent
+        assert_eq!(checked_target_from_bits(0x0080_0000), Some(U256::zero()));
+        assert_eq!(checked_target_from_bits(0xff00_0000), Some(U256::zero()));
+
+        // Each of these would be easily met by the genesis hash if it were accepted
+        let genesis = genesis(Network::Mainnet);
+        let with_bits = |bits| BlockHeader { bits, ..genesis.clone() };
+
+        // Sign bit set
+        assert_eq!(checked_target_from_bits(0x2080_ffff), None);
+        assert!(!with_bits(0x2080_ffff).check_pow());
+
+        // One bit past 256, for each mantissa size
+        for bits in [0x2300_0001, 0x2200_0100, 0x2101_0000] {
+            assert_eq!(checked_target_from_bits(bits), None, "{bits:x}");
+            assert!(!with_bits(bits).check_pow(), "{bits:x}");
+        }
+        assert!(checked_target_from_bits(0x2200_00ff).is_some());
+
+        // Regtest's limit, far above mainnet's
+        assert!(U256::from_little_endian(&genesis.hash()) <= target_from_bits(0x207f_ffff));
+        assert!(!with_bits(0x207f_ffff).check_pow());
+        // Just above mainnet's limit
+        assert!(!with_bits(0x1d01_0000).check_pow());
+
+        // Zero target
+        assert!(!with_bits(0).check_pow());
+    }
+
+    #[test]
+    fn test_parse_merkle_block() {
+        // Testnet block 00000000000000cac712b726e4326e596170574c01a16001692510c44025eb30
+        let message = "00000020df3b053dc46f162a9b00c7f0d5124e2676d47bbe7c5d0793a50000000000\
+            0000ef445fef2ed495c275892206ca533e7411907971013ab83e3b47bd0d692d14d4dc7c835b67d8001a\
+            c157e670bf0d00000aba412a0d1480e370173072c9562becffe87aa661c1e4a6dbc305d38ec5dc088a7c\
+            f92e6458aca7b32edae818f9c2c98c37e06bf72ae0ce80649a38655ee1e27d34d9421d940b16732f24b9\
+            4023e9d572a7f9ab8023434a4feb532d2adfc8c2c2158785d1bd04eb99df2e86c54bc13e139862897217\
+            400def5d72c280222c4cbaee7261831e1550dbb8fa82853e9fe506fc5fda3f7b919d8fe74b6282f92763\
+            cef8e625f977af7c8619c32a369b832bc2d051ecd9c73c51e76370ceabd4f25097c256597fa898d404ed\
+            53425de608ac6bfe426f6e2bb457f1c554866eb69dcb8d6bf6f880e9a59b3cd053e6c7060eeacaacf4da\
+            c6697dac20e4bd3f38a2ea2543d1ab7953e3430790a9f81e1c67f5b58c825acf46bd02848384eebe9af9\
+            17274cdfbb1a28a5d58a23a17977def0de10d644258d9c54f886d47d293a411cb6226103b55635"
+            .to_bytes_be();
+
+        let (header, matched) = parse_merkle_block(&message).unwrap();
+        assert_eq!(
+            format_hash_display(&header.hash()),
+            "00000000000000cac712b726e4326e596170574c01a16001692510c44025eb30"
+        );
+        assert_eq!(
+            matched,
+            [parse_hash_display("6122b61c413a297dd486f8549c8d2544d610def0de7779a1238ad5a5281abbdf")
+                .unwrap()]
+        );
+
+        let mut trailing = message.clone();
+        trailing.push(0);
+        assert_eq!(parse_merkle_block(&trailing), Err(BlockError::InvalidLength));
+        assert_eq!(
+            parse_merkle_block(&message[..message.len() - 1]),
+            Err(BlockError::InvalidLength)
+        );
+
+        // A different hash changes the root
+        let mut tampered = message.clone();
+        tampered[100] ^= 1;
+        assert_eq!(parse_merkle_block(&tampered), Err(BlockError::MerkleRootMismatch));
+
+        // An extra flag byte is never read
+        let mut extra_flags = message[..message.len() - 4].to_vec();
+        extra_flags.extend_from_slice(&[0x04, 0xb5, 0x56, 0x35, 0x00]);
+        assert_eq!(parse_merkle_block(&extra_flags), Err(BlockError::InvalidPartialTree));
+
+        let mut no_transactions = message;
+        no_transactions[80..84].copy_from_slice(&[0; 4]);
+        assert_eq!(
+            parse_merkle_block(&no_transactions),
+            Err(BlockError::InvalidTransactionCount(0))
+        );
+    }
+
+    #[test]
+    fn test_parse_merkle_block_all_matched() {
+        let txids: Vec<[u8; 32]> = (1..=3).map(|i| [i; 32]).collect();
+        let header = BlockHeader {
+            version: 1,
+            prev_block: [0; 32],
+            merkle_root: merkle_root(&txids),
+            time: 0,
+            bits: 0x1d00_ffff,
+            nonce: 0,
+        };
+
+        // Root, both inner nodes and all three leaves are flagged: 0b0011_1111
+        let mut message = header.serialize().to_vec();
+        message.extend_from_slice(&3u32.to_le_bytes());
+        message.push(3);
+        for txid in &txids {
+            message.extend_from_slice(txid);
+        }
+        message.extend_from_slice(&[1, 0x3f]);
+        assert_eq!(parse_merkle_block(&message), Ok((header.clone(), txids.clone())));
+
+        // The last leaf duplicated as its own sibling is rejected
+        let duplicated = [txids[0], txids[1], txids[2], txids[2]];
+        let header = BlockHeader { merkle_root: merkle_root(&duplicated), ..header };
+        let mut message = header.serialize().to_vec();
+        message.extend_from_slice(&4u32.to_le_bytes());
+        message.push(4);
+        for txid in &duplicated {
+            message.extend_from_slice(txid);
+        }
+        message.extend_from_slice(&[1, 0x7f]);
+        assert_eq!(parse_merkle_block(&message), Err(BlockError::InvalidPartialTree));
+    }
+}
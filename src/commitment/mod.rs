@@ -0,0 +1,81 @@
+#![allow(unused)]
+use crate::elliptic_curve::secp256k1::Secp256k1Point;
+use num_bigint::BigUint;
+use primitive_types::U256;
+use std::sync::LazyLock;
+
+/// Domain separating the second generator from other uses of hash-to-curve
+const GENERATOR_H_DOMAIN: &[u8] = b"bitcoin-rs/pedersen/H";
+
+static GENERATOR_H: LazyLock<Secp256k1Point> = LazyLock::new(|| {
+    Secp256k1Point::hash_to_curve(&Secp256k1Point::g().to_sec(true), GENERATOR_H_DOMAIN)
+});
+
+/// Returns the second generator `H`, hashed to the curve from the SEC encoding of G
+///
+/// Nobody knows the discrete log of H with respect to G, which is what keeps a
+/// commitment from being opened to two different values.
+pub fn generator_h() -> Secp256k1Point {
+    GENERATOR_H.clone()
+}
+
+/// Commits to a value, `C = value * G + blinding * H`
+///
+/// The commitment hides the value as long as the blinding factor is random and secret.
+/// Commitments add up: the sum of two commits to the sum of their values under the sum
+/// of their blinding factors. Both are reduced modulo the group order.
+pub fn pedersen(
+    value: U256,
+    blinding: U256,
+    g: &Secp256k1Point,
+    h: &Secp256k1Point,
+) -> Secp256k1Point {
+    g * to_biguint(value) + h * to_biguint(blinding)
+}
+
+/// Checks that a commitment opens to the value and blinding factor
+pub fn verify_open(
+    commitment: &Secp256k1Point,
+    value: U256,
+    blinding: U256,
+    g: &Secp256k1Point,
+    h: &Secp256k1Point,
+) -> bool {
+    pedersen(value, blinding, g, h) == *commitment
+}
+
+fn to_biguint(value: U256) -> BigUint {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    BigUint::from_bytes_be(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pedersen_homomorphic() {
+        let g = Secp256k1Point::g();
+        let h = generator_h();
+        assert_ne!(g, h);
+
+        let (v1, r1) = (U256::from(21_000u64), U256::from(0x1234_5678_9abc_def0_u64));
+        let (v2, r2) = (U256::from(1_000_000u64), U256::MAX >> 2);
+
+        let sum = pedersen(v1, r1, &g, &h) + pedersen(v2, r2, &g, &h);
+        assert_eq!(sum, pedersen(v1 + v2, r1 + r2, &g, &h));
+    }
+
+    #[test]
+    fn test_verify_open() {
+        let g = Secp256k1Point::g();
+        let h = generator_h();
+        let (value, blinding) = (U256::from(50_000u64), U256::from(987_654_321u64));
+        let commitment = pedersen(value, blinding, &g, &h);
+
+        assert!(verify_open(&commitment, value, blinding, &g, &h));
+        assert!(!verify_open(&commitment, value + 1, blinding, &g, &h));
+        assert!(!verify_open(&commitment, value, blinding + 1, &g, &h));
+    }
+}
@@ -1,15 +1,28 @@
 use super::point::{Point, PointType};
-use crate::finite_fields::element::Felt;
-use color_eyre::eyre::Result;
+use crate::finite_fields::{element::Felt, pow::Pow};
+use color_eyre::eyre::{eyre, Result};
+use num_bigint::BigUint;
 
 /// Represents an elliptic curve
 /// ( y^2 = x^3 + ax + b )
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct Curve {
     pub a: Felt,
     pub b: Felt,
 }
 
+/// Curves are equal when a, b and the prime of their field are
+///
+/// `Felt` equality only looks at the values, so the primes are compared separately.
+impl PartialEq for Curve {
+    fn eq(&self, other: &Self) -> bool {
+        self.a == other.a
+            && self.b == other.b
+            && self.a.prime() == other.a.prime()
+            && self.b.prime() == other.b.prime()
+    }
+}
+
 impl Curve {
     /// Creates a new curve (y^2 = x^3 + ax + b) from a and b
     pub fn new(a: Felt, b: Felt) -> Self {
@@ -25,6 +38,53 @@ impl Curve {
         Point::try_from_felts(x, y, self.a.clone(), self.b.clone())
     }
 
+    /// Finds the point on the curve with the given x coordinate and y parity
+    ///
+    /// This is what point decompression does: y^2 is computed from the curve
+    /// equation and its square root is taken with `Felt::sqrt`, so it works for
+    /// any prime field, not only the ones where `p ≡ 3 (mod 4)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no point on the curve with the given x
+    pub fn lift_x(&self, x: Felt, is_odd: bool) -> Result<Point> {
+        let y_squared = x.pow(3u32) + &self.a * &x + &self.b;
+        let root = y_squared
+            .sqrt()
+            .ok_or_else(|| eyre!("No point on the curve with given x"))?;
+
+        self.point(x, root.with_parity(is_odd))
+    }
+
+    /// Counts the points on the curve, including the identity
+    ///
+    /// Goes over every x in the field and checks whether `x^3 + ax + b` is a square, so
+    /// this is only meant for small teaching curves. It takes forever on secp256k1.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the prime doesn't fit in a `u64`
+    pub fn count_points(&self) -> u64 {
+        let prime = self.a.prime().clone();
+        let size = u64::try_from(&prime).expect("Field is too large to count points");
+
+        // Identity
+        let mut count = 1;
+        for x in 0..size {
+            let x = Felt::new(BigUint::from(x), prime.clone());
+            let y_squared = x.pow(3u32) + &self.a * &x + &self.b;
+
+            if y_squared.inner() == &BigUint::from(0u32) {
+                count += 1;
+            } else if y_squared.is_quadratic_residue() {
+                // (x, y) and (x, -y)
+                count += 2;
+            }
+        }
+
+        count
+    }
+
     /// Returns the identity point of the curve which is (Inf, Inf)
     pub fn identity(&self) -> Point {
         Point::new(PointType::Infinity, PointType::Infinity, self.clone())
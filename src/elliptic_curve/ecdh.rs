@@ -0,0 +1,18 @@
+use super::{private_key::PrivateKey, secp256k1::Secp256k1Point};
+use sha2::{Digest, Sha256};
+
+/// Computes the ECDH shared secret between a private key and someone else's public key
+///
+/// Matches Bitcoin Core's (libsecp256k1) default: the shared point `secret * public`
+/// is serialized in compressed SEC format and hashed with SHA-256. The multiplication
+/// goes through `Secp256k1Point::mul_secret` since the scalar is secret.
+///
+/// Returns `None` when the shared point is the identity, e.g. for an identity public
+/// key, which libsecp256k1 reports as a failure too.
+pub fn shared_secret(private: &PrivateKey, public: &Secp256k1Point) -> Option<[u8; 32]> {
+    let shared_point = public.mul_secret(private.secret().inner());
+    if shared_point.is_identity() {
+        return None;
+    }
+    Some(Sha256::digest(shared_point.to_sec(true)).into())
+}
@@ -0,0 +1,103 @@
+use super::{
+    curve::Curve,
+    point::{Point, PointType},
+};
+use crate::finite_fields::{element::Felt, pow::Pow};
+use num_bigint::BigUint;
+
+/// A point in Jacobian coordinates, `(X, Y, Z)` standing for `(X / Z^2, Y / Z^3)`
+///
+/// Adding and doubling affine points takes a field inversion every time. Keeping the
+/// denominator in Z defers it, so a chain of operations only inverts once at the end.
+/// The identity has `Z = 0`.
+#[derive(Debug, Clone)]
+pub struct JacobianPoint {
+    pub x: Felt,
+    pub y: Felt,
+    pub z: Felt,
+    pub curve: Curve,
+}
+
+impl JacobianPoint {
+    /// Converts an affine point, with `Z = 1`
+    pub fn from_affine(point: &Point) -> Self {
+        let prime = point.curve.a.prime().clone();
+        let felt = |value: u32| Felt::new(BigUint::from(value), prime.clone());
+
+        match point.coordinates() {
+            Some((x, y)) => Self { x, y, z: felt(1), curve: point.curve.clone() },
+            None => Self { x: felt(1), y: felt(1), z: felt(0), curve: point.curve.clone() },
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.z == Felt::new(BigUint::from(0u32), self.z.prime().clone())
+    }
+
+    /// Returns `2 * self`
+    ///
+    /// `S = 4XY^2`, `M = 3X^2 + aZ^4`, then `X' = M^2 - 2S`, `Y' = M(S - X') - 8Y^4` and
+    /// `Z' = 2YZ`. Points with `Y = 0` double to the identity, which falls out of `Z' = 0`.
+    #[allow(clippy::many_single_char_names)]
+    pub fn double(&self) -> Self {
+        let yy = self.y.pow(2u32);
+        let s = &self.x * &yy * 4u32;
+        let m = self.x.pow(2u32) * 3u32 + &self.curve.a * self.z.pow(4u32);
+
+        let x = m.pow(2u32) - &s * 2u32;
+        let y = &m * (&s - &x) - yy.pow(2u32) * 8u32;
+        let z = &self.y * &self.z * 2u32;
+        Self { x, y, z, curve: self.curve.clone() }
+    }
+
+    /// Converts back to affine coordinates, taking one field inversion
+    pub fn to_affine(&self) -> Point {
+        if self.is_identity() {
+            return self.curve.identity();
+        }
+        self.to_affine_with_inverse(&self.z.invert_or_zero())
+    }
+
+    /// Converts many points to affine coordinates with a single field inversion
+    ///
+    /// Uses Montgomery's trick: the running products `z_1 * ... * z_i` are inverted
+    /// together, and each `z_i^-1` is peeled off that inverse with two multiplications.
+    /// Identities are skipped, they have nothing to invert.
+    pub fn batch_to_affine(points: &[JacobianPoint]) -> Vec<Point> {
+        let Some(first) = points.first() else {
+            return Vec::new();
+        };
+        let one = Felt::new(BigUint::from(1u32), first.z.prime().clone());
+
+        // prefixes[i] is the product of the Z coordinates before point i
+        let mut prefixes = Vec::with_capacity(points.len());
+        let mut product = one;
+        for point in points {
+            prefixes.push(product.clone());
+            if !point.is_identity() {
+                product = product * &point.z;
+            }
+        }
+
+        let mut inverse = product.invert_or_zero();
+        let mut affine = vec![None; points.len()];
+        for (i, point) in points.iter().enumerate().rev() {
+            if point.is_identity() {
+                affine[i] = Some(point.curve.identity());
+                continue;
+            }
+            // inverse is (z_1 * ... * z_i)^-1 here
+            affine[i] = Some(point.to_affine_with_inverse(&(&inverse * &prefixes[i])));
+            inverse = inverse * &point.z;
+        }
+
+        affine.into_iter().flatten().collect()
+    }
+
+    fn to_affine_with_inverse(&self, z_inverse: &Felt) -> Point {
+        let z_inverse_squared = z_inverse.pow(2u32);
+        let x = &self.x * &z_inverse_squared;
+        let y = &self.y * (z_inverse_squared * z_inverse);
+        Point::new(PointType::Normal(x), PointType::Normal(y), self.curve.clone())
+    }
+}
@@ -1,19 +1,27 @@
 #![allow(unused)]
 pub mod curve;
+pub mod ecdh;
+pub mod jacobian;
+pub mod mul_cache;
 pub mod point;
 pub mod secp256k1;
 pub mod signature;
+pub mod signature_cache;
 pub mod private_key;
+pub mod public_key;
+pub mod scalar;
+pub mod schnorr;
 
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
-    use crate::{finite_fields::macros::felt, elliptic_curve::{secp256k1::Secp256k1Felt, signature::Signature}, helpers::bytes::ToBytesBigEndian};
+    use crate::{finite_fields::macros::felt, elliptic_curve::{private_key::{PrivateKey, WifError}, public_key::{Network, PublicKey}, scalar::Scalar, secp256k1::Secp256k1Felt, signature::{DerError, Signature}}, helpers::{bytes::ToBytesBigEndian, hash::hash256}, testvectors};
     use num_bigint::BigUint;
     use primitive_types::U256;
+    use sha2::{Digest, Sha256, Sha512};
 
-    use super::{curve::Curve, point::Point, secp256k1::Secp256k1Point, *};
+    use super::{curve::Curve, mul_cache::PointMulCache, point::Point, secp256k1::Secp256k1Point, *};
 
     #[test]
     fn test_curve() {
@@ -47,6 +55,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::useless_vec, clippy::cast_possible_truncation)]
     fn test_scalar() {
         let scalar_multiples = vec![
             (47, 71),
@@ -99,6 +108,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::cast_sign_loss)]
     fn test_binary_expansion() {
         let prime = 223u64;
         let curve = Curve::new(felt!(0, prime), felt!(7, prime));
@@ -115,79 +125,1161 @@ mod tests {
 
     #[test]
     fn test_secp256k1_values() {
-        // The fact that this works means point is on the curve
+        for vector in testvectors::secp256k1_points() {
+            // The fact that this works means point is on the curve
+            let scalar = BigUint::from_str(&vector.scalar).unwrap();
+            let point: Point = (Secp256k1Point::g() * scalar).into();
+            let (x, y) = point.coordinates().unwrap();
+
+            // Compare point values with string representations of the values
+            assert_eq!(&BigUint::from_str(&vector.x).unwrap(), x.inner());
+            assert_eq!(&BigUint::from_str(&vector.y).unwrap(), y.inner());
+
+            assert_eq!(
+                BigUint::from_str(
+                    "115792089237316195423570985008687907853269984665640564039457584007908834671663"
+                )
+                .unwrap(),
+                point.curve.a.prime().to_owned()
+            );
+        }
+    }
+
+    #[test]
+    fn test_secp256k1_scalar() {
         let point = Secp256k1Point::g();
-        let point: Point = point.clone().into();
+        let identity: Point = (point * Secp256k1Point::order()).into();
+
+        assert_eq!(identity, Secp256k1Point::curve().identity());
+    }
+
+    #[test]
+    fn test_signature_validation() {
+        for vector in testvectors::ecdsa() {
+            let point = Secp256k1Point::new(
+                BigUint::parse_bytes(vector.public_x.as_bytes(), 16).unwrap(),
+                BigUint::parse_bytes(vector.public_y.as_bytes(), 16).unwrap(),
+            );
+
+            let z = Scalar::from_bytes(&vector.z.to_bytes_be());
+            let signature = Signature::new(
+                Scalar::from_bytes(&vector.r.to_bytes_be()),
+                Scalar::from_bytes(&vector.s.to_bytes_be()),
+            );
+
+            assert_eq!(point.verify(&z, &signature), vector.valid);
+        }
+    }
+
+    #[test]
+    fn test_lift_x() {
+        // 17 ≡ 1 (mod 4) so the square root needs Tonelli–Shanks
+        let prime = 17u64;
+        let curve = Curve::new(felt!(0, prime), felt!(7, prime));
+
+        let odd = curve.lift_x(felt!(1, prime), true).unwrap();
+        let even = curve.lift_x(felt!(1, prime), false).unwrap();
+        assert_eq!(odd, curve.point(felt!(1, prime), felt!(5, prime)).unwrap());
+        assert_eq!(even, curve.point(felt!(1, prime), felt!(12, prime)).unwrap());
 
-        // Compare point values with string representations of the values
+        let point = curve.lift_x(felt!(15, prime), false).unwrap();
+        assert_eq!(point, curve.point(felt!(15, prime), felt!(4, prime)).unwrap());
+
+        assert!(curve.lift_x(felt!(4, prime), true).is_err());
+    }
+
+    #[test]
+    fn test_sec() {
+        let g = Secp256k1Point::g();
+
+        let compressed = g.to_sec(true);
         assert_eq!(
-            BigUint::from_str(
-                "55066263022277343669578718895168534326250603453777594175500187360389116729240"
+            compressed,
+            "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798".to_bytes_be()
+        );
+        assert_eq!(Secp256k1Point::from_sec(&compressed).unwrap(), g);
+
+        let point = g * 12345u32;
+        assert_eq!(Secp256k1Point::from_sec(&point.to_sec(true)).unwrap(), point);
+        assert_eq!(Secp256k1Point::from_sec(&point.to_sec(false)).unwrap(), point);
+
+        assert!(Secp256k1Point::from_sec(&compressed[1..]).is_err());
+    }
+
+    #[test]
+    fn test_ecdh_shared_secret() {
+        let alice = PrivateKey::new(Scalar::new(BigUint::from(12345u32)));
+        let bob = PrivateKey::new(Scalar::new(BigUint::from(67890u32)));
+
+        let alice_secret = ecdh::shared_secret(&alice, bob.public_key()).unwrap();
+        let bob_secret = ecdh::shared_secret(&bob, alice.public_key()).unwrap();
+        assert_eq!(alice_secret, bob_secret);
+
+        let eve = PrivateKey::new(Scalar::new(BigUint::from(13579u32)));
+        assert_ne!(ecdh::shared_secret(&eve, bob.public_key()).unwrap(), alice_secret);
+
+        let identity = Secp256k1Point::g() * Secp256k1Point::order();
+        assert!(identity.is_identity());
+        assert_eq!(ecdh::shared_secret(&alice, &identity), None);
+    }
+
+    #[test]
+    fn test_ecdh_known_answer() {
+        // Shared point computed independently with OpenSSL, then hashed as libsecp256k1's
+        // default hash function does: SHA-256 of the compressed point
+        let private = PrivateKey::new(Scalar::new(
+            BigUint::parse_bytes(
+                b"c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721",
+                16,
             )
             .unwrap(),
-            point.x.unwrap().inner().to_owned()
+        ));
+        let public = Secp256k1Point::from_hex(
+            "025b142a4ea3e8d75dae35a73861d693a06bff7b12268cb0a29df873eef8b65890",
+        )
+        .unwrap();
+        assert_eq!(
+            ecdh::shared_secret(&private, &public).unwrap().to_vec(),
+            "1fc58c5030d62693fe63e92b39852fd1af73f0b871c2626ac8a2fc70276d027d".to_bytes_be()
         );
 
+        // libsecp256k1's generator test: with G as the public key the secret is the hash of
+        // the compressed public key
+        let public_key = private.public_key().to_sec(true);
         assert_eq!(
-            BigUint::from_str(
-                "32670510020758816978083085130507043184471273380659243275938904335757337482424"
-            )
-            .unwrap(),
-            point.y.unwrap().inner().to_owned()
+            ecdh::shared_secret(&private, &Secp256k1Point::g()).unwrap().to_vec(),
+            Sha256::digest(public_key).to_vec()
         );
+    }
 
+    #[test]
+    fn test_point_order() {
+        let generator: Point = Secp256k1Point::g().into();
         assert_eq!(
-            BigUint::from_str(
-                "115792089237316195423570985008687907853269984665640564039457584007908834671663"
-            )
-            .unwrap(),
-            point.curve.a.prime().to_owned()
+            generator.order(&Secp256k1Point::order()).unwrap(),
+            Secp256k1Point::order()
         );
+
+        // y^2 = x^3 + 7 over F_223 has 252 points
+        let prime = 223u64;
+        let curve = Curve::new(felt!(0, prime), felt!(7, prime));
+        let curve_order = BigUint::from(252u32);
+
+        let point = curve.point(felt!(15, prime), felt!(86, prime)).unwrap();
+        assert_eq!(point.order(&curve_order).unwrap(), BigUint::from(7u32));
+
+        let point = curve.point(felt!(47, prime), felt!(71, prime)).unwrap();
+        assert_eq!(point.order(&curve_order).unwrap(), BigUint::from(21u32));
+
+        assert!(point.order(&BigUint::from(20u32)).is_err());
     }
 
     #[test]
-    fn test_secp256k1_scalar() {
-        let point = Secp256k1Point::g();
-        let identity: Point = (point * Secp256k1Point::order()).into();
+    fn test_sign_blinded() {
+        let wallet = PrivateKey::new(Scalar::new(BigUint::from(12345u32)));
+        let z = Scalar::from_bytes(&hash256(b"Programming Bitcoin!"));
 
-        assert_eq!(identity, Secp256k1Point::curve().identity());
+        let signature = wallet.sign(&z);
+        let blinded = wallet.sign_blinded(&z);
+
+        assert!(wallet.verify(&z, &signature));
+        assert!(wallet.verify(&z, &blinded));
+        assert_eq!(signature, blinded);
     }
 
     #[test]
-    fn test_signature_validation() {
-        let point_x = BigUint::parse_bytes(
-            b"887387e452b8eacc4acfde10d9aaf7f6d9a0f975aabb10d006e4da568744d06c",
+    fn test_deterministic_k() {
+        let z = BigUint::from_bytes_be(&Sha256::digest(b"Satoshi Nakamoto"));
+
+        let wallet = PrivateKey::new(Scalar::new(BigUint::from(1u32)));
+        let expected = BigUint::parse_bytes(
+            b"8f8a276c19f4149656b280621e358cce24f5f52542772691ee69063b74f15d15",
             16,
         )
         .unwrap();
+        assert_eq!(wallet.deterministic_k(z.clone()), expected);
+        assert_eq!(wallet.deterministic_k_with::<Sha256>(z.clone()), expected);
 
-        let point_y = BigUint::parse_bytes(
-            b"61de6d95231cd89026e286df3b6ae4a894a3378e393e93a0f45b666329a0ae34",
+        let expected = BigUint::parse_bytes(
+            b"b610aa329497ed0d43a6e0bbf6fac7e5b72533c2ea3a6732a8fe44a0c655ed45",
             16,
         )
         .unwrap();
+        assert_eq!(wallet.deterministic_k_with::<Sha512>(z.clone()), expected);
+
+        let secret = Secp256k1Point::order() - BigUint::from(1u32);
+        let wallet = PrivateKey::new(Scalar::new(secret));
+        let expected = BigUint::parse_bytes(
+            b"33a19b60e25fb6f4435af53a3d42d493644827367e6453928554f43e49aa6f90",
+            16,
+        )
+        .unwrap();
+        assert_eq!(wallet.deterministic_k(z), expected);
+    }
+
+    #[test]
+    fn test_deterministic_k_with_counter() {
+        let z = BigUint::from_bytes_be(&Sha256::digest(b"Satoshi Nakamoto"));
+        let wallet = PrivateKey::new(Scalar::new(BigUint::from(1u32)));
+
+        assert_eq!(
+            wallet.deterministic_k_with_counter(z.clone(), 0),
+            wallet.deterministic_k(z.clone())
+        );
+
+        let nonces: Vec<BigUint> =
+            (0..4).map(|counter| wallet.deterministic_k_with_counter(z.clone(), counter)).collect();
+        for (i, nonce) in nonces.iter().enumerate() {
+            assert!(nonces[i + 1..].iter().all(|other| other != nonce), "{i}");
+        }
+        assert_eq!(wallet.deterministic_k_with_counter(z.clone(), 2), nonces[2]);
+
+        // The same counter on another message is another nonce
+        let other = BigUint::from_bytes_be(&Sha256::digest(b"Programming Bitcoin!"));
+        assert_ne!(wallet.deterministic_k_with_counter(other, 2), nonces[2]);
+    }
+
+    #[test]
+    fn test_public_key_address() {
+        let point = Secp256k1Point::g() * BigUint::from(0x0001_2345_dead_beef_u64);
+
+        let mainnet = PublicKey::new(point.clone(), Network::Mainnet);
+        assert_eq!(mainnet.address(), "1F1Pn2y6pDb68E5nYJJeba4TLg2U7B6KF1");
+
+        let testnet = PublicKey::new(point, Network::Testnet);
+        assert_eq!(testnet.address(), "muXM5645dF2LuLZQFsH2RVGnCfdB4vR1bB");
+    }
+
+    #[test]
+    fn test_recover_all() {
+        let wallet = PrivateKey::new(Scalar::new(BigUint::from(12345u32)));
+        let z = Scalar::from_bytes(&hash256(b"Programming Bitcoin!"));
+        let signature = wallet.sign(&z);
+
+        let candidates = signature.recover_all(&z);
+        assert!(!candidates.is_empty());
+        assert!(candidates.contains(wallet.public_key()));
+    }
+
+    #[test]
+    fn test_signature_bytes() {
+        let wallet = PrivateKey::new(Scalar::new(BigUint::from(12345u32)));
+        let signature = wallet.sign_slice(b"Programming Bitcoin!");
+
+        let bytes = signature.to_bytes();
+        assert_eq!(Signature::from_bytes(&bytes).unwrap(), signature);
+
+        // s == n is out of range
+        let mut bytes = bytes;
+        bytes[32..].copy_from_slice(&Secp256k1Point::order().to_bytes_be());
+        assert!(Signature::from_bytes(&bytes).is_err());
+    }
 
-        let point = Secp256k1Point::new(point_x, point_y);
+    #[test]
+    fn test_point_mul_cache() {
+        let g: Point = Secp256k1Point::g().into();
+        let h: Point = (Secp256k1Point::g() * 12345u32).into();
+        let scalars = [
+            BigUint::from(0u32),
+            BigUint::from(1u32),
+            BigUint::from(0xdead_beef_u32),
+            Secp256k1Point::order() - BigUint::from(1u32),
+        ];
+
+        let mut cache = PointMulCache::new(1);
+        for scalar in &scalars {
+            assert_eq!(cache.mul(&g, scalar), g.binary_expansion_biguint(scalar.clone()));
+        }
+        assert!(cache.contains(&g));
+
+        // Capacity is 1 so caching h evicts g
+        assert_eq!(cache.mul(&h, &scalars[2]), h.binary_expansion_biguint(scalars[2].clone()));
+        assert!(cache.contains(&h));
+        assert!(!cache.contains(&g));
+        assert_eq!(cache.len(), 1);
+    }
+
+    /// Times repeated multiplications of one point with and without its cached window
+    /// table, run with `cargo test --release bench_point_mul_cache -- --ignored --nocapture`
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_point_mul_cache() {
+        let point: Point = (Secp256k1Point::g() * 12345u32).into();
+        let scalars: Vec<BigUint> = (1u32..=20)
+            .map(|i| BigUint::from_bytes_be(&Sha256::digest(i.to_le_bytes())))
+            .collect();
+
+        let now = std::time::Instant::now();
+        let uncached: Vec<Point> = scalars
+            .iter()
+            .map(|scalar| std::hint::black_box(Point::windowed_mul(&point.window_table(), scalar)))
+            .collect();
+        println!("uncached: {:?}", now.elapsed());
+
+        let mut cache = PointMulCache::new(1);
+        let now = std::time::Instant::now();
+        let cached: Vec<Point> = scalars
+            .iter()
+            .map(|scalar| std::hint::black_box(cache.mul(&point, scalar)))
+            .collect();
+        println!("cached: {:?}", now.elapsed());
+
+        assert_eq!(cached, uncached);
+    }
 
-        let good_z = Secp256k1Felt::from_bytes(
-            &"ec208baa0fc1c19f708a9ca96fdeff3ac3f230bb4a7ba4aede4942ad003c0f60".to_bytes_be(),
+    #[test]
+    fn test_signature_der() {
+        let high_r = Scalar::from_bytes(
+            &"8000000000000000000000000000000000000000000000000000000000000001".to_bytes_be(),
+        );
+        let low_r = Scalar::from_bytes(
+            &"7f00000000000000000000000000000000000000000000000000000000000001".to_bytes_be(),
         );
+        let s = Scalar::from_bytes(&"0123".to_bytes_be());
+
+        // High bit set: 0x00 is prepended so the integer isn't negative
+        let signature = Signature::new(high_r, s.clone());
+        let der = signature.to_der();
+        assert_eq!(der[..5], [0x30, 0x27, 0x02, 0x21, 0x00]);
+        assert_eq!(der[37..], [0x02, 0x02, 0x01, 0x23]);
+        assert_eq!(Signature::from_der(&der).unwrap(), signature);
+
+        // High bit not set: no padding
+        let signature = Signature::new(low_r, s);
+        let der = signature.to_der();
+        assert_eq!(der[..5], [0x30, 0x26, 0x02, 0x20, 0x7f]);
+        assert_eq!(Signature::from_der(&der).unwrap(), signature);
+
+        // Padding an integer that doesn't need it is rejected
+        let mut padded = vec![0x30, 0x27, 0x02, 0x21, 0x00];
+        padded.extend_from_slice(&der[4..]);
+        assert_eq!(Signature::from_der(&padded), Err(DerError::NonMinimalInteger));
+
+        // Known signature from Programming Bitcoin
+        let der = "3045022037206a0610995c58074999cb9767b87af4c4978db68c06e8e6e81d282047a7c60221008ca63759c1157ebeaec0d03cecca119fc9a75bf8e6d0fa65c841c8e2738cdaec".to_bytes_be();
+        assert_eq!(Signature::from_der(&der).unwrap().to_der(), der);
+    }
+
+    #[test]
+    fn test_der_length_mismatch() {
+        let der = "3045022037206a0610995c58074999cb9767b87af4c4978db68c06e8e6e81d282047a7c60221008ca63759c1157ebeaec0d03cecca119fc9a75bf8e6d0fa65c841c8e2738cdaec".to_bytes_be();
+
+        // Length byte one short of the content
+        let mut short = der.clone();
+        short[1] = 0x44;
+        assert_eq!(
+            Signature::from_der(&short),
+            Err(DerError::LengthMismatch { declared: 0x44, actual: 0x45 })
+        );
+
+        // Trailing garbage, with and without the length byte covering it
+        let mut trailing = der.clone();
+        trailing.push(0x01);
+        assert_eq!(
+            Signature::from_der(&trailing),
+            Err(DerError::LengthMismatch { declared: 0x45, actual: 0x46 })
+        );
+        trailing[1] = 0x46;
+        assert_eq!(Signature::from_der(&trailing), Err(DerError::TrailingBytes));
+
+        // s claiming more bytes than the sequence has left
+        let mut long_s = der;
+        long_s[37] = 0x22;
+        assert_eq!(Signature::from_der(&long_s), Err(DerError::UnexpectedEnd));
+    }
+
+    #[test]
+    fn test_secp256k1_generator_cached() {
+        assert!(std::ptr::eq(Secp256k1Point::generator(), Secp256k1Point::generator()));
+        assert_eq!(&Secp256k1Point::g(), Secp256k1Point::generator());
+        assert_eq!(Secp256k1Point::g().x(), Secp256k1Point::g().x());
+        assert_eq!(Secp256k1Point::g().y(), Secp256k1Point::g().y());
+    }
+
+    #[test]
+    fn test_public_key_fingerprint() {
+        // Master key of BIP32 test vector 1
+        let secret = BigUint::parse_bytes(
+            b"e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35",
+            16,
+        )
+        .unwrap();
+        let wallet = PrivateKey::new(Scalar::new(secret));
+        let public_key = PublicKey::new(wallet.public_key().clone(), Network::Mainnet);
+
+        assert_eq!(
+            public_key.identifier().to_vec(),
+            "3442193e1bb70916e914552172cd4e2dbc9df811".to_bytes_be()
+        );
+        assert_eq!(public_key.fingerprint(), [0x34, 0x42, 0x19, 0x3e]);
+    }
+
+    #[test]
+    fn test_verify_malleable() {
+        let wallet = PrivateKey::new(Scalar::new(BigUint::from(12345u32)));
+        let z = Scalar::from_bytes(&hash256(b"Programming Bitcoin!"));
+        let signature = wallet.sign(&z);
+        assert!(signature.is_low_s());
+
+        let high_s = Scalar::new(Secp256k1Point::order() - signature.s().inner());
+        let malleated = Signature::new(signature.r().clone(), high_s);
+        assert!(!malleated.is_low_s());
+
+        assert!(malleated.verify_malleable(&z, wallet.public_key()));
+        assert!(!malleated.verify(&z, wallet.public_key()));
+        assert!(signature.verify(&z, wallet.public_key()));
+    }
+
+    #[test]
+    #[allow(clippy::many_single_char_names)]
+    fn test_scalar_arithmetic() {
+        let n = Secp256k1Point::order();
+        let a = &n - BigUint::from(5u32);
+        let b = BigUint::parse_bytes(
+            b"c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5",
+            16,
+        )
+        .unwrap();
+
+        let x = Scalar::new(a.clone());
+        let y = Scalar::new(b.clone());
+
+        assert_eq!((&x + &y).inner(), &((&a + &b) % &n));
+        assert_eq!((&x * &y).inner(), &((&a * &b) % &n));
+        assert_eq!((&y - &x).inner(), &((&b + &n - &a) % &n));
+        assert_eq!((-&x).inner(), &BigUint::from(5u32));
+
+        // Values are always reduced modulo n, not p
+        assert_eq!(Scalar::new(&n + BigUint::from(3u32)), Scalar::from(3));
+        assert_eq!(Scalar::new(n.clone()), Scalar::default());
+
+        let y_inverse = y.inverse().unwrap();
+        assert_eq!(&y * &y_inverse, Scalar::from(1));
+        assert_eq!(&x / &y * &y, x);
+        assert!(Scalar::default().inverse().is_none());
+    }
+
+    #[test]
+    fn test_aggregate_s() {
+        let wallet = PrivateKey::new(Scalar::new(BigUint::from(12345u32)));
+        let signatures: Vec<_> = [b"first".as_slice(), b"second", b"third"]
+            .iter()
+            .map(|message| wallet.sign_slice(message))
+            .collect();
+
+        let n = Secp256k1Point::order();
+        let expected = signatures
+            .iter()
+            .fold(BigUint::from(0u32), |sum, signature| (sum + signature.s().inner()) % &n);
+
+        assert_eq!(Signature::aggregate_s(&signatures).inner(), &expected);
+        assert_eq!(Signature::aggregate_s(&[]), Scalar::default());
+    }
+
+    #[test]
+    fn test_parsers_reject_truncated_input() {
+        let wallet = PrivateKey::new(Scalar::from(12345));
+        let sec = wallet.public_key().to_sec(false);
+        let der = wallet.sign_slice(b"Programming Bitcoin!").to_der();
+
+        for length in 0..sec.len() {
+            assert!(Secp256k1Point::from_sec(&sec[..length]).is_err());
+        }
+        for length in 0..der.len() {
+            assert!(Signature::from_der(&der[..length]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_select_ct() {
+        let curve = Curve::new(felt!(0, 223), felt!(7, 223));
+        let point = curve.point(felt!(47, 223), felt!(71, 223)).unwrap();
+        let table = point.window_table();
+
+        for window in 0..table.len() {
+            assert_eq!(Point::select_ct(&table, window), table[window]);
+        }
+
+        // Short coefficients are padded to 256 bits, full width ones are not. The small
+        // curve hits every special case of the addition formulas on the way.
+        let full_width = (BigUint::from(1u32) << 256u32) - BigUint::from(1u32);
+        let coefficients = (0..50u32).map(BigUint::from).chain([
+            BigUint::from(0xdead_beef_u32),
+            full_width.clone(),
+            full_width + BigUint::from(2u32),
+        ]);
+        for coefficient in coefficients {
+            assert_eq!(
+                Point::windowed_mul_ct(&table, &coefficient),
+                Point::windowed_mul(&table, &coefficient),
+                "{coefficient}"
+            );
+        }
+
+        // Selecting to and from the identity
+        let mut selected = curve.identity();
+        selected.conditional_assign(&point, subtle::Choice::from(0));
+        assert!(selected.is_identity());
+        selected.conditional_assign(&point, subtle::Choice::from(1));
+        assert_eq!(selected, point);
+        selected.conditional_assign(&curve.identity(), subtle::Choice::from(1));
+        assert_eq!(selected, curve.identity());
+
+        let secret = BigUint::from(0x0001_2345_dead_beef_u64);
+        assert_eq!(Secp256k1Point::mul_generator(&secret), Secp256k1Point::g() * &secret);
+        let point = Secp256k1Point::g() * 12345u32;
+        assert_eq!(point.mul_secret(&secret), &point * &secret);
+    }
+
+    #[test]
+    fn test_count_points() {
+        let curve = Curve::new(felt!(0, 223), felt!(7, 223));
+        assert_eq!(curve.count_points(), 252);
+
+        // y^2 = x^3 + x + 1 over F_5: (0, ±1), (2, ±1), (3, ±1), (4, ±2) and the identity
+        let curve = Curve::new(felt!(1, 5), felt!(1, 5));
+        assert_eq!(curve.count_points(), 9);
+    }
+
+    #[test]
+    fn test_is_valid_xonly() {
+        let g_x: [u8; 32] = Secp256k1Point::g().to_sec(true)[1..].try_into().unwrap();
+        assert!(schnorr::is_valid_xonly(&g_x));
+
+        let p: [u8; 32] = Secp256k1Felt::prime().to_bytes_be().try_into().unwrap();
+        assert!(!schnorr::is_valid_xonly(&p));
+
+        // 5^3 + 7 is not a square modulo p
+        let mut five = [0u8; 32];
+        five[31] = 5;
+        assert!(!schnorr::is_valid_xonly(&five));
+    }
+
+    #[test]
+    fn test_hash_to_curve() {
+        let point = Secp256k1Point::hash_to_curve(b"Programming Bitcoin!", b"test-domain");
+        assert_eq!(point, Secp256k1Point::hash_to_curve(b"Programming Bitcoin!", b"test-domain"));
+
+        // Decoding checks the point is on the curve
+        assert_eq!(Secp256k1Point::from_sec(&point.to_sec(true)).unwrap(), point);
+        assert!(!point.y().clone().unwrap().inner().bit(0));
+
+        assert_ne!(point, Secp256k1Point::hash_to_curve(b"Programming Bitcoin", b"test-domain"));
+        assert_ne!(point, Secp256k1Point::hash_to_curve(b"Programming Bitcoin!", b"other-domain"));
+    }
 
-        let signature = Signature::new(
-            Secp256k1Felt::from_bytes(
-                &"ac8d1c87e51d0d441be8b3dd5b05c8795b48875dffe00b7ffcfac23010d3a395".to_bytes_be(),
-            ),
-            Secp256k1Felt::from_bytes(
-                &"068342ceff8935ededd102dd876ffd6ba72d6a427a3edb13d26eb0781cb423c4".to_bytes_be(),
+    #[test]
+    fn test_point_add_ref() {
+        let g = Secp256k1Point::g();
+        let h = Secp256k1Point::g() * 12345u32;
+        let expected = g.clone() + h.clone();
+
+        assert_eq!(&g + &h, expected);
+        assert_eq!(g.clone() + &h, expected);
+        assert_eq!(&g + h.clone(), expected);
+        assert_eq!(&g + &g, g.clone() + g.clone());
+
+        let (g, h): (Point, Point) = (g.into_inner(), h.into_inner());
+        assert_eq!(g.add_ref(&h), expected.into_inner());
+        assert_eq!(g.add_ref(&g.curve.identity()), g);
+        assert_eq!(g.curve.identity().add_ref(&g), g);
+    }
+
+    /// Times double and add with owned, cloned points against `add_ref` on references, run
+    /// with `cargo test --release bench_point_add_ref -- --ignored --nocapture`
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_point_add_ref() {
+        let g: Point = Secp256k1Point::g().into();
+        let scalars: Vec<BigUint> = (1u32..=20)
+            .map(|i| BigUint::from_bytes_be(&Sha256::digest(i.to_le_bytes())))
+            .collect();
+
+        let now = std::time::Instant::now();
+        let cloned: Vec<Point> = scalars
+            .iter()
+            .map(|scalar| {
+                let mut current = g.clone();
+                let mut result = g.curve.identity();
+                for bit in 0..scalar.bits() {
+                    if scalar.bit(bit) {
+                        result = result.clone() + current.clone();
+                    }
+                    current = current.clone() + current.clone();
+                }
+                std::hint::black_box(result)
+            })
+            .collect();
+        println!("owned clones: {:?}", now.elapsed());
+
+        let now = std::time::Instant::now();
+        let referenced: Vec<Point> = scalars
+            .iter()
+            .map(|scalar| std::hint::black_box(g.binary_expansion_biguint(scalar.clone())))
+            .collect();
+        println!("add_ref: {:?}", now.elapsed());
+
+        assert_eq!(cloned, referenced);
+    }
+
+    #[test]
+    fn test_sign_recoverable() {
+        for secret in [1u32, 12345] {
+            let wallet = PrivateKey::new(Scalar::from(secret));
+            for message in [b"Programming Bitcoin!".as_slice(), b"second"] {
+                let z = Scalar::from_bytes(&hash256(message));
+                let (signature, recovery_id) = wallet.sign_recoverable(&z);
+
+                assert_eq!(signature, wallet.sign(&z));
+                assert_eq!(&signature.recover(&z, recovery_id).unwrap(), wallet.public_key());
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate_private_key() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(12345);
+        let n = Secp256k1Point::order();
+        let keys: Vec<_> = (0..5).map(|_| PrivateKey::generate(&mut rng)).collect();
+
+        for (i, key) in keys.iter().enumerate() {
+            assert!(!key.secret().is_zero());
+            assert!(key.secret().inner() < &n);
+            assert_eq!(key.public_key(), &(Secp256k1Point::g() * key.secret().inner()));
+            for other in &keys[i + 1..] {
+                assert_ne!(key.secret(), other.secret());
+            }
+        }
+
+        // The same seed gives the same keys
+        let mut rng = StdRng::seed_from_u64(12345);
+        assert_eq!(PrivateKey::generate(&mut rng).secret(), keys[0].secret());
+    }
+
+    #[test]
+    fn test_curve_eq() {
+        let secp256k1 = Secp256k1Point::curve();
+        let rebuilt = Curve::new(
+            Secp256k1Felt::new(BigUint::from(0u32)).into(),
+            Secp256k1Felt::new(BigUint::from(7u32)).into(),
+        );
+        assert_eq!(secp256k1, rebuilt);
+
+        // Same a and b in a different field
+        let small = Curve::new(felt!(0, 223), felt!(7, 223));
+        assert_ne!(secp256k1, small);
+        assert_ne!(small, Curve::new(felt!(0, 227), felt!(7, 227)));
+        assert_ne!(small, Curve::new(felt!(1, 223), felt!(7, 223)));
+    }
+
+    #[test]
+    fn test_is_identity() {
+        let curve = Curve::new(felt!(0, 223), felt!(7, 223));
+        let point = curve.point(felt!(47, 223), felt!(71, 223)).unwrap();
+
+        assert!(curve.identity().is_identity());
+        assert!(!point.is_identity());
+        assert!((point * 21u32).is_identity());
+
+        assert!(!Secp256k1Point::g().is_identity());
+        assert!((Secp256k1Point::g() * (Secp256k1Point::order() - BigUint::from(1u32))
+            + Secp256k1Point::g())
+        .is_identity());
+    }
+
+    #[test]
+    fn test_coordinates() {
+        let generator: Point = Secp256k1Point::g().into();
+        let (x, y) = generator.coordinates().unwrap();
+        assert_eq!(
+            x.inner(),
+            &BigUint::parse_bytes(
+                b"79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+                16
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            y.inner(),
+            &BigUint::parse_bytes(
+                b"483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+                16
             )
+            .unwrap()
+        );
+
+        assert_eq!(generator.curve.identity().coordinates(), None);
+    }
+
+    #[test]
+    fn test_wif() {
+        let private_key = PrivateKey::new(Scalar::new(BigUint::from(1u32)));
+        assert_eq!(
+            private_key.to_wif(Network::Mainnet, true),
+            "KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWn"
+        );
+        assert_eq!(
+            private_key.to_wif(Network::Mainnet, false),
+            "5HpHagT65TZzG1PH3CSu63k8DbpvD8s5ip4nEB3kEsreAnchuDf"
         );
 
-        assert_eq!(point.verify(&good_z, &signature), true);
+        for network in [Network::Mainnet, Network::Testnet] {
+            for compressed in [true, false] {
+                let wif = private_key.to_wif(network, compressed);
+                let (decoded, decoded_network, decoded_compressed) =
+                    PrivateKey::from_wif(&wif).unwrap();
+                assert_eq!(decoded.public_key(), private_key.public_key());
+                assert_eq!((decoded_network, decoded_compressed), (network, compressed));
+            }
+        }
+
+        // Zero, the group order and an unknown compression flag
+        let n = crate::helpers::bytes::to_32_bytes_be(&Secp256k1Point::order());
+        for (payload, error) in [
+            ([&[0x80][..], &[0; 32]].concat(), WifError::InvalidSecret),
+            ([&[0x80][..], &n].concat(), WifError::InvalidSecret),
+            ([&[0x80][..], &[1; 32], &[0x02]].concat(), WifError::InvalidCompressionFlag(2)),
+            ([&[0x00][..], &[1; 32]].concat(), WifError::UnknownVersion(0)),
+            (vec![0x80; 20], WifError::InvalidLength(20)),
+        ] {
+            let wif = crate::helpers::base58::encode_check(&payload);
+            assert_eq!(PrivateKey::from_wif(&wif).unwrap_err(), error);
+        }
+
+        // Last character changed, which breaks the checksum
+        let corrupted = "KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWm";
+        assert_eq!(PrivateKey::from_wif(corrupted).unwrap_err(), WifError::InvalidBase58);
+    }
+
+    #[test]
+    fn test_wif_for_network() {
+        let private_key = PrivateKey::new(Scalar::new(BigUint::from(12345u32)));
+        let mainnet = private_key.to_wif(Network::Mainnet, true);
+        let testnet = private_key.to_wif(Network::Testnet, false);
+
+        let (decoded, compressed) =
+            PrivateKey::from_wif_for_network(&mainnet, Network::Mainnet).unwrap();
+        assert_eq!(decoded.public_key(), private_key.public_key());
+        assert!(compressed);
 
-        let bad_z = Secp256k1Felt::from_bytes(
-            &"bad000aa0fc1c19f708a9ca96fdeff3ac3f230bb4a7ba4aede4942ad003c0f60".to_bytes_be(),
+        let (_, compressed) = PrivateKey::from_wif_for_network(&testnet, Network::Testnet).unwrap();
+        assert!(!compressed);
+
+        assert_eq!(
+            PrivateKey::from_wif_for_network(&testnet, Network::Mainnet).unwrap_err(),
+            WifError::NetworkMismatch { expected: Network::Mainnet, actual: Network::Testnet }
+        );
+        assert_eq!(
+            PrivateKey::from_wif_for_network(&mainnet, Network::Testnet).unwrap_err(),
+            WifError::NetworkMismatch { expected: Network::Testnet, actual: Network::Mainnet }
         );
+    }
+
+    #[test]
+    fn test_schnorr_verify_components() {
+        // First BIP340 test vector, secret key 3 and an all zero message
+        let pubkey_x: [u8; 32] = "f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9"
+            .to_bytes_be()
+            .try_into()
+            .unwrap();
+        let signature: [u8; 64] = "e907831f80848d1069a5371b402410364bdf1c5f8307b0084c55f1ce2dca8215\
+            25f66a4a85ea8b71e482a74f382d2ce5ebeee8fdb2172f477df4900d310536c0"
+            .to_bytes_be()
+            .try_into()
+            .unwrap();
+        let message = [0u8; 32];
+
+        let (nonce_point, e, public_key) =
+            schnorr::verify_components(&pubkey_x, &message, &signature).unwrap();
+        assert_eq!(public_key, Secp256k1Point::g() * BigUint::from(3u32));
+
+        let s = BigUint::from_bytes_be(&signature[32..]);
+        assert_eq!(Secp256k1Point::g() * s, nonce_point + public_key.clone() * e.inner());
+
+        // A different message changes the challenge, so the equation no longer holds
+        let (nonce_point, e, _) =
+            schnorr::verify_components(&pubkey_x, &[1u8; 32], &signature).unwrap();
+        let s = BigUint::from_bytes_be(&signature[32..]);
+        assert_ne!(Secp256k1Point::g() * s, nonce_point + public_key * e.inner());
+
+        // s at the group order and a key that isn't on the curve are malformed
+        let n = crate::helpers::bytes::to_32_bytes_be(&Secp256k1Point::order());
+        let mut high_s = signature;
+        high_s[32..].copy_from_slice(&n);
+        assert!(schnorr::verify_components(&pubkey_x, &message, &high_s).is_none());
+        let mut five = [0u8; 32];
+        five[31] = 5;
+        assert!(schnorr::verify_components(&five, &message, &signature).is_none());
+    }
+
+    #[test]
+    fn test_point_from_coordinates() {
+        let generator = Secp256k1Point::from_coordinates(
+            "55066263022277343669578718895168534326250603453777594175500187360389116729240",
+            "32670510020758816978083085130507043184471273380659243275938904335757337482424",
+        )
+        .unwrap();
+        assert_eq!(generator, Secp256k1Point::g());
+
+        let hex = Secp256k1Point::from_coordinates(
+            "0x79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+            "0x483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+        )
+        .unwrap();
+        assert_eq!(hex, Secp256k1Point::g());
+
+        for vector in testvectors::secp256k1_points() {
+            let scalar = BigUint::from_str(&vector.scalar).unwrap();
+            let point = Secp256k1Point::from_coordinates(&vector.x, &vector.y).unwrap();
+            assert_eq!(point, Secp256k1Point::g() * scalar);
+        }
+
+        // Off the curve, outside the field and not a number
+        assert!(Secp256k1Point::from_coordinates("1", "2").is_err());
+        let p = Secp256k1Felt::prime().to_string();
+        assert!(Secp256k1Point::from_coordinates(&p, "0").is_err());
+        assert!(Secp256k1Point::from_coordinates("0xzz", "1").is_err());
+        assert!(Secp256k1Point::from_coordinates("", "1").is_err());
+    }
+
+    #[test]
+    fn test_private_key_from_seed() {
+        let key = PrivateKey::from_seed_u64(42);
+        let again = PrivateKey::from_seed_u64(42);
+        assert_eq!(key.secret(), again.secret());
+        assert_eq!(key.public_key(), again.public_key());
+
+        // SHA256 of the little endian seed
+        let expected = Sha256::digest(42u64.to_le_bytes());
+        assert_eq!(key.secret(), &Scalar::from_bytes(&expected));
+
+        assert_ne!(PrivateKey::from_seed_u64(43).public_key(), key.public_key());
+        assert_ne!(PrivateKey::from_seed_u64(0).public_key(), key.public_key());
+    }
+
+    #[test]
+    fn test_sec_hybrid() {
+        // G has an even y
+        let g = Secp256k1Point::g();
+        let hybrid = g.to_sec_hybrid();
+        assert_eq!(hybrid[0], 0x06);
+        assert_eq!(hybrid[1..], g.to_sec(false)[1..]);
+        assert_eq!(Secp256k1Point::from_sec(&hybrid).unwrap(), g);
+
+        for scalar in 1u32..8 {
+            let point = Secp256k1Point::g() * scalar;
+            let hybrid = point.to_sec_hybrid();
+            assert_eq!(hybrid[0] & 1, point.to_sec(true)[0] & 1);
+            assert_eq!(Secp256k1Point::from_sec(&hybrid).unwrap(), point);
+
+            // Flipping the prefix contradicts y
+            let mut flipped = hybrid.clone();
+            flipped[0] ^= 1;
+            assert!(Secp256k1Point::from_sec(&flipped).is_err());
+            assert!(Secp256k1Point::from_sec(&hybrid[..64]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_jacobian_batch_to_affine() {
+        use super::jacobian::JacobianPoint;
+        use crate::finite_fields::pow::Pow;
+
+        let curve = Curve::new(felt!(0, 223), felt!(7, 223));
+        let point = curve.point(felt!(47, 223), felt!(71, 223)).unwrap();
+
+        // The same point with different Z, doublings of it and the identity
+        let mut points = Vec::new();
+        for lambda in [1u32, 2, 100, 222] {
+            let lambda = felt!(lambda, 223);
+            let affine = JacobianPoint::from_affine(&point);
+            points.push(JacobianPoint {
+                x: &affine.x * lambda.pow(2u32),
+                y: &affine.y * lambda.pow(3u32),
+                z: lambda,
+                curve: curve.clone(),
+            });
+        }
+        let mut doubled = JacobianPoint::from_affine(&point);
+        for _ in 0..6 {
+            doubled = doubled.double();
+            points.push(doubled.clone());
+        }
+        points.insert(2, JacobianPoint::from_affine(&curve.identity()));
+
+        let batch = JacobianPoint::batch_to_affine(&points);
+        let one_by_one: Vec<Point> = points.iter().map(JacobianPoint::to_affine).collect();
+        assert_eq!(batch, one_by_one);
+
+        assert_eq!(batch[0], point);
+        assert_eq!(batch[4], point);
+        assert!(batch[2].is_identity());
+        for (k, affine) in batch[5..].iter().enumerate() {
+            assert_eq!(affine, &(point.clone() * (2u32 << k)));
+        }
+
+        assert!(JacobianPoint::batch_to_affine(&[]).is_empty());
+
+        // Doubling on secp256k1 matches affine doubling
+        let g: Point = Secp256k1Point::g().into();
+        let jacobian = JacobianPoint::from_affine(&g).double().double();
+        assert_eq!(jacobian.to_affine(), g * 4u32);
+    }
+
+    #[test]
+    fn test_p2pkh_address_with_mock_hasher() {
+        use crate::helpers::{
+            base58,
+            hash::{Hasher, StandardHasher},
+        };
+        use std::cell::RefCell;
+
+        // Returns fixed digests and records what it was asked to hash
+        #[derive(Default)]
+        struct MockHasher {
+            hash160_inputs: RefCell<Vec<Vec<u8>>>,
+            hash256_inputs: RefCell<Vec<Vec<u8>>>,
+        }
+
+        impl Hasher for MockHasher {
+            fn hash256(&self, data: &[u8]) -> [u8; 32] {
+                self.hash256_inputs.borrow_mut().push(data.to_vec());
+                let mut digest = [0u8; 32];
+                digest.iter_mut().zip(0u8..).for_each(|(byte, i)| *byte = i);
+                digest
+            }
+
+            fn hash160(&self, data: &[u8]) -> [u8; 20] {
+                self.hash160_inputs.borrow_mut().push(data.to_vec());
+                [0xab; 20]
+            }
+        }
+
+        let point = Secp256k1Point::g() * BigUint::from(0x0001_2345_dead_beef_u64);
+        let public_key = PublicKey::new(point.clone(), Network::Testnet);
+
+        let hasher = MockHasher::default();
+        let address = public_key.p2pkh_address_with(&hasher, false);
+
+        // prefix || hash160(sec) || hash256(prefix || hash160(sec))[..4]
+        let mut payload = vec![0x6f];
+        payload.extend_from_slice(&[0xab; 20]);
+        assert_eq!(*hasher.hash160_inputs.borrow(), vec![point.to_sec(false)]);
+        assert_eq!(*hasher.hash256_inputs.borrow(), vec![payload.clone()]);
+        payload.extend_from_slice(&[0, 1, 2, 3]);
+        assert_eq!(address, base58::encode(&payload));
+        assert_eq!(base58::decode_check_with(&hasher, &address).unwrap(), payload[..21]);
+        assert!(base58::decode_check(&address).is_err());
+
+        // The segwit program is the mocked identifier of the compressed key
+        let address = public_key.p2wpkh_address_with(&hasher);
+        assert_eq!(public_key.identifier_with(&hasher), [0xab; 20]);
+        assert_eq!(
+            crate::helpers::bech32::decode_segwit(&address).unwrap(),
+            (0, vec![0xab; 20])
+        );
+
+        // The standard hasher gives the real address
+        assert_eq!(
+            public_key.p2pkh_address_with(&StandardHasher, true),
+            "muXM5645dF2LuLZQFsH2RVGnCfdB4vR1bB"
+        );
+    }
+
+    #[test]
+    fn test_secp256k1_curve_parameters() {
+        use crate::finite_fields::element::Felt;
+
+        let prime = U256::from_str_radix(
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+            16,
+        )
+        .unwrap();
+        assert_eq!(Secp256k1Point::field_prime(), prime);
+
+        assert_eq!(Secp256k1Point::a(), Felt::new(BigUint::from(0u32), Secp256k1Felt::prime()));
+        assert_eq!(Secp256k1Point::b(), Felt::new(BigUint::from(7u32), Secp256k1Felt::prime()));
+        assert_eq!(Secp256k1Point::a(), Secp256k1Point::curve().a);
+    }
+
+    #[test]
+    fn test_key_info() {
+        let private_key = PrivateKey::new(Scalar::new(BigUint::from(1u32)));
+        let info = private_key.key_info(Network::Mainnet);
+
+        assert_eq!(info.compressed_sec, Secp256k1Point::g().to_sec(true));
+        assert_eq!(info.uncompressed_sec, Secp256k1Point::g().to_sec(false));
+        assert_eq!(
+            info.hash160.to_vec(),
+            "751e76e8199196d454941c45d1b3a323f1433bd6".to_bytes_be()
+        );
+        assert_eq!(info.p2pkh, "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH");
+        assert_eq!(info.p2wpkh, "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4");
+
+        let public_key = PublicKey::new(private_key.public_key().clone(), Network::Testnet);
+        let info = private_key.key_info(Network::Testnet);
+        assert_eq!(info.hash160, public_key.identifier());
+        assert_eq!(info.p2pkh, public_key.address());
+        assert_eq!(info.p2wpkh, "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx");
+    }
+
+    #[test]
+    fn test_p2tr_address() {
+        // First receive address of the BIP86 test vectors
+        let (private_key, _, _) =
+            PrivateKey::from_wif("KyRv5iFPHG7iB5E4CqvMzH3WFJVhbfYK4VY7XAedd9Ys69mEsPLQ").unwrap();
+        assert_eq!(
+            private_key.public_key().to_sec(true)[1..],
+            "cc8a4bc64d897bddc5fbc2f670f7a8ba0b386779106cf1223c6fc5d7cd6fc115".to_bytes_be()
+        );
+        assert_eq!(
+            private_key.p2tr_address(Network::Mainnet),
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
+        assert!(private_key.p2tr_address(Network::Testnet).starts_with("tb1p"));
+    }
+
+    #[test]
+    fn test_sum_mod_order() {
+        let to_biguint = |value: &U256| {
+            let mut bytes = [0u8; 32];
+            value.to_big_endian(&mut bytes);
+            BigUint::from_bytes_be(&bytes)
+        };
+
+        // Large enough that the plain sum overflows 256 bits
+        let scalars = [U256::MAX, U256::MAX - 1, U256::from(12345u32), U256::MAX >> 1];
+        let sum = scalar::sum_mod_order(&scalars);
+        assert!(to_biguint(&sum) < Secp256k1Point::order());
+
+        let points = scalars
+            .iter()
+            .map(|value| Secp256k1Point::g() * to_biguint(value))
+            .reduce(|sum, point| sum + point)
+            .unwrap();
+        assert_eq!(Secp256k1Point::g() * to_biguint(&sum), points);
+
+        assert_eq!(scalar::sum_mod_order(&[]), U256::zero());
+        let n = U256::from_big_endian(&Secp256k1Point::order().to_bytes_be());
+        assert_eq!(scalar::sum_mod_order(&[n - 1, U256::one()]), U256::zero());
+    }
+
+    #[test]
+    fn test_point_hex() {
+        use crate::helpers::hex;
+
+        let g_hex = "0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+        let g = Secp256k1Point::from_hex(g_hex).unwrap();
+        assert_eq!(g, Secp256k1Point::g());
+        assert_eq!(g.to_hex_compressed(), g_hex);
+
+        // Uppercase and uncompressed parse too
+        assert_eq!(Secp256k1Point::from_hex(&g_hex.to_uppercase()).unwrap(), g);
+        let uncompressed = hex::encode(&g.to_sec(false));
+        assert_eq!(Secp256k1Point::from_hex(&uncompressed).unwrap(), g);
+
+        let point = Secp256k1Point::g() * BigUint::from(0xdead_beef_u32);
+        assert_eq!(Secp256k1Point::from_hex(&point.to_hex_compressed()).unwrap(), point);
+
+        assert!(Secp256k1Point::from_hex(&g_hex[..64]).is_err());
+        assert!(Secp256k1Point::from_hex(&format!("{g_hex}00")).is_err());
+        assert!(Secp256k1Point::from_hex(&g_hex.replace('7', "g")).is_err());
+        assert!(Secp256k1Point::from_hex(&uncompressed[..66]).is_err());
+    }
+
+    #[test]
+    fn test_signature_cache() {
+        use super::signature_cache::SignatureCache;
+
+        let private_key = PrivateKey::new(Scalar::new(BigUint::from(12345u32)));
+        let public_key = private_key.public_key();
+        let z = Scalar::from_bytes(&hash256(b"Programming Bitcoin!"));
+        let signature = private_key.sign(&z);
+
+        // A miss verifies for real and caches the result
+        let mut cache = SignatureCache::new(2);
+        assert_eq!(cache.get(&z, &signature, public_key), None);
+        assert!(cache.verify(&z, &signature, public_key));
+        assert_eq!(cache.get(&z, &signature, public_key), Some(true));
+
+        let other_z = Scalar::from(1u32);
+        assert!(!cache.verify(&other_z, &signature, public_key));
+        assert_eq!(cache.get(&other_z, &signature, public_key), Some(false));
+        assert_eq!(cache.len(), 2);
+
+        // A hit returns whatever is stored, without verifying again
+        cache.insert(&z, &signature, public_key, false);
+        assert!(!cache.verify(&z, &signature, public_key));
+        assert_eq!(cache.len(), 2);
+
+        // A third result evicts the oldest
+        let third_z = Scalar::from(2u32);
+        cache.verify(&third_z, &signature, public_key);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&z, &signature, public_key), None);
+        assert!(cache.verify(&z, &signature, public_key));
+
+        let mut cache = SignatureCache::new(0);
+        assert!(cache.verify(&z, &signature, public_key));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_bip340_vectors() {
+        let vectors = testvectors::bip340();
+        assert_eq!(vectors.len(), 19);
+
+        for vector in vectors {
+            let public_key: [u8; 32] = vector.public_key.to_bytes_be().try_into().unwrap();
+            let signature: [u8; 64] = vector.signature.to_bytes_be().try_into().unwrap();
+            assert_eq!(
+                schnorr::verify(&public_key, &vector.message.to_bytes_be(), &signature),
+                vector.valid,
+                "{}: {}",
+                vector.index,
+                vector.comment
+            );
+
+            // Vectors with a secret key also pin down the public key it gives
+            if !vector.secret_key.is_empty() {
+                let secret = Scalar::from_bytes(&vector.secret_key.to_bytes_be());
+                let private_key = PrivateKey::new(secret);
+                assert_eq!(
+                    private_key.public_key().to_sec(true)[1..],
+                    public_key,
+                    "{}",
+                    vector.index
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_negative_of() {
+        let p: Point = (Secp256k1Point::g() * 12345u32).into();
+        let q: Point = (Secp256k1Point::g() * 54321u32).into();
+        assert!(p.is_negative_of(&-&p));
+        assert!((-&p).is_negative_of(&p));
+        assert!(!p.is_negative_of(&p));
+        assert!(!p.is_negative_of(&-&q));
+        assert!((&p + &-&p).is_identity());
+
+        let identity = p.curve.identity();
+        assert!(identity.is_negative_of(&-&identity));
+        assert!(!p.is_negative_of(&identity));
+
+        // (6, 0) is its own negative
+        let prime = 223u64;
+        let curve = Curve::new(felt!(0, prime), felt!(7, prime));
+        let point = curve.point(felt!(6, prime), felt!(0, prime)).unwrap();
+        assert!(point.is_negative_of(&point));
+        assert_eq!(-&point, point);
 
-        assert_eq!(point.verify(&bad_z, &signature), false);
+        // Same coordinates on another curve
+        let other_curve = Curve::new(felt!(1, prime), felt!(1, prime));
+        let a = curve.point(felt!(47, prime), felt!(71, prime)).unwrap();
+        assert!(!a.is_negative_of(&Point { curve: other_curve, ..-&a }));
     }
 }
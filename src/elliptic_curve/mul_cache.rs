@@ -0,0 +1,64 @@
+use super::point::Point;
+use num_bigint::BigUint;
+use std::collections::VecDeque;
+
+/// Cache of window tables for points that are multiplied over and over
+///
+/// Building a window table costs a few additions per entry, which pays off when the
+/// same point is multiplied by many scalars, like public keys during verification.
+/// Holds up to `capacity` tables and evicts the least recently used one when full.
+#[derive(Debug, Clone)]
+pub struct PointMulCache {
+    capacity: usize,
+    tables: VecDeque<(Point, Vec<Point>)>,
+}
+
+impl PointMulCache {
+    /// Creates an empty cache that keeps at most `capacity` window tables
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tables: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of cached tables
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    /// Returns whether the cache has no tables
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// Returns whether there is a table cached for the point
+    pub fn contains(&self, point: &Point) -> bool {
+        self.tables.iter().any(|(cached, _)| cached == point)
+    }
+
+    /// Multiplies the point by the coefficient, building and caching its window table
+    /// if it's not cached yet
+    pub fn mul(&mut self, point: &Point, coefficient: &BigUint) -> Point {
+        if self.capacity == 0 {
+            return Point::windowed_mul(&point.window_table(), coefficient);
+        }
+
+        let cached = self
+            .tables
+            .iter()
+            .position(|(cached, _)| cached == point)
+            .and_then(|index| self.tables.remove(index));
+
+        let entry = cached.unwrap_or_else(|| {
+            if self.tables.len() == self.capacity {
+                self.tables.pop_back();
+            }
+            (point.clone(), point.window_table())
+        });
+
+        let result = Point::windowed_mul(&entry.1, coefficient);
+        self.tables.push_front(entry);
+        result
+    }
+}
@@ -2,7 +2,14 @@ use super::curve::Curve;
 use crate::finite_fields::{element::Felt, macros::impl_refs, pow::Pow};
 use color_eyre::eyre::{eyre, Result};
 use num_bigint::BigUint;
-use std::ops::{Add, BitAnd, Mul};
+use std::ops::{Add, BitAnd, Mul, Neg};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+
+/// Number of coefficient bits handled at once by windowed multiplication
+pub const WINDOW_BITS: u32 = 4;
+
+/// Coefficient width `windowed_mul_ct` always processes, enough for any secp256k1 scalar
+const CT_COEFFICIENT_BITS: u32 = 256;
 
 /// Represents type of a point on an elliptic curve
 ///
@@ -51,6 +58,37 @@ impl Point {
         }
     }
 
+    /// Returns whether the point is the identity, the point at infinity
+    pub fn is_identity(&self) -> bool {
+        matches!(self.x, PointType::Infinity)
+    }
+
+    /// Returns the affine `(x, y)` coordinates, or `None` for the identity
+    pub fn coordinates(&self) -> Option<(Felt, Felt)> {
+        match (&self.x, &self.y) {
+            (PointType::Normal(x), PointType::Normal(y)) => Some((x.clone(), y.clone())),
+            _ => None,
+        }
+    }
+
+    /// Returns whether `self + other` is the identity
+    ///
+    /// That is when both are on the same curve and have the same x and opposite y, or
+    /// both are the identity. Points with `y = 0` are their own negatives.
+    pub fn is_negative_of(&self, other: &Point) -> bool {
+        if self.curve != other.curve {
+            return false;
+        }
+
+        match (self.coordinates(), other.coordinates()) {
+            (None, None) => true,
+            (Some((x1, y1)), Some((x2, y2))) => {
+                x1 == x2 && (y1 + y2).inner() == &BigUint::from(0u32)
+            }
+            _ => false,
+        }
+    }
+
     /// Creates a new point from point types
     ///
     /// # Panics
@@ -112,6 +150,51 @@ impl Point {
         }
     }
 
+    /// Returns the order of the point, given the order of the group it belongs to
+    ///
+    /// The order of a point divides the group order, so starting from the group order
+    /// prime factors are divided out for as long as the point still multiplies to the
+    /// identity. Factors are found by trial division below 2^16 and the rest is assumed
+    /// to be prime, which is exact for small curves and for prime order groups like
+    /// secp256k1's, where this is just a subgroup check.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `curve_order * self` is not the identity
+    pub fn order(&self, curve_order: &BigUint) -> Result<BigUint> {
+        if !(self.clone() * curve_order.clone()).is_identity() {
+            return Err(eyre!("Point is not in a group of the given order"));
+        }
+
+        let zero = BigUint::from(0u32);
+        let one = BigUint::from(1u32);
+        let trial_limit = BigUint::from(1u32 << 16);
+
+        let mut factors = Vec::new();
+        let mut remaining = curve_order.clone();
+        let mut factor = BigUint::from(2u32);
+        while factor < trial_limit && &factor * &factor <= remaining {
+            while &remaining % &factor == zero {
+                remaining /= &factor;
+                factors.push(factor.clone());
+            }
+            factor += &one;
+        }
+        if remaining > one {
+            factors.push(remaining);
+        }
+
+        let mut order = curve_order.clone();
+        for factor in factors {
+            let candidate = &order / &factor;
+            if (self.clone() * candidate.clone()).is_identity() {
+                order = candidate;
+            }
+        }
+
+        Ok(order)
+    }
+
     pub fn naive_mul(&self, coefficient: u32) -> Self {
         let mut product = Point::new(PointType::Infinity, PointType::Infinity, self.curve.clone());
 
@@ -141,6 +224,125 @@ impl Point {
         result
     }
 
+    /// Returns `[0 * P, 1 * P, ..., (2^WINDOW_BITS - 1) * P]` for windowed multiplication
+    pub fn window_table(&self) -> Vec<Self> {
+        let mut table = Vec::with_capacity(1 << WINDOW_BITS);
+        table.push(self.curve.identity());
+        for i in 1..1 << WINDOW_BITS {
+            let next = &table[i - 1] + self;
+            table.push(next);
+        }
+        table
+    }
+
+    /// Scalar multiplies a point given its window table
+    ///
+    /// Goes over the coefficient `WINDOW_BITS` bits at a time from the most significant
+    /// end, doubling `WINDOW_BITS` times and adding the table entry for each window.
+    /// With the table built once this needs far fewer additions than binary expansion.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table is empty
+    pub fn windowed_mul(table: &[Self], coefficient: &BigUint) -> Self {
+        let mut result = table[0].clone();
+        let windows = coefficient.to_radix_be(1 << WINDOW_BITS);
+
+        for (i, &window) in windows.iter().enumerate() {
+            if i > 0 {
+                for _ in 0..WINDOW_BITS {
+                    result = &result + &result;
+                }
+            }
+            result = result + &table[window as usize];
+        }
+
+        result
+    }
+
+    /// Sets `self` to `src` when `choice` is set, without branching on it
+    ///
+    /// The coordinates go through `Felt::conditional_assign`, with zero standing in for
+    /// the identity's, and whether the result is the identity is selected as a byte.
+    /// Only turning that byte back into a `PointType` branches, on the selected value.
+    pub fn conditional_assign(&mut self, src: &Self, choice: Choice) {
+        let zero = Felt::new(BigUint::from(0u32), self.curve.a.prime().clone());
+        let coordinates = |point: &Self| point.coordinates().unwrap_or((zero.clone(), zero.clone()));
+        let (mut x, mut y) = coordinates(self);
+        let (src_x, src_y) = coordinates(src);
+        x.conditional_assign(&src_x, choice);
+        y.conditional_assign(&src_y, choice);
+
+        let mut identity = u8::from(self.is_identity());
+        identity.conditional_assign(&u8::from(src.is_identity()), choice);
+        (self.x, self.y) = if identity == 1 {
+            (PointType::Infinity, PointType::Infinity)
+        } else {
+            (PointType::Normal(x), PointType::Normal(y))
+        };
+    }
+
+    /// Returns `table[index]`, reading every entry of the table
+    ///
+    /// Indexing the table directly touches a different memory location for each window
+    /// value, which leaks a secret coefficient through cache timing. Here every entry is
+    /// read and kept or dropped with `conditional_assign` on a `subtle` comparison.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table is empty
+    pub fn select_ct(table: &[Self], index: usize) -> Self {
+        let mut selected = table[0].clone();
+        for (i, entry) in table.iter().enumerate() {
+            selected.conditional_assign(entry, i.ct_eq(&index));
+        }
+        selected
+    }
+
+    /// Scalar multiplies a point like `windowed_mul`, looking up the table with `select_ct`
+    ///
+    /// Use this when the coefficient is secret. The windows are padded with leading zeros
+    /// to a full 256 bits, and no step short-circuits on the identity:
+    ///
+    /// - The accumulator starts at `P = table[1]` instead of the identity, and the
+    ///   resulting `2^(4 * windows) * P` is subtracted at the end.
+    /// - A zero window still adds `P` and then keeps the old accumulator with
+    ///   `conditional_assign`, so every window does one real addition.
+    ///
+    /// Special cases of the addition formulas can still be hit when the accumulator meets
+    /// the table entry, which is negligible on secp256k1 but common on small teaching
+    /// curves, where the result is still correct. `BigUint` arithmetic itself isn't
+    /// constant time either.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table has fewer than two entries
+    pub fn windowed_mul_ct(table: &[Self], coefficient: &BigUint) -> Self {
+        let point = &table[1];
+        let digits = coefficient.to_radix_be(1 << WINDOW_BITS);
+        let padding = (CT_COEFFICIENT_BITS / WINDOW_BITS) as usize;
+        let windows: Vec<u8> = std::iter::repeat_n(0, padding.saturating_sub(digits.len()))
+            .chain(digits)
+            .collect();
+
+        let mut result = point.clone();
+        let mut offset = point.clone();
+        for &window in &windows {
+            for _ in 0..WINDOW_BITS {
+                result = &result + &result;
+                offset = &offset + &offset;
+            }
+
+            let is_zero = window.ct_eq(&0);
+            let mut addend = Self::select_ct(table, usize::from(window));
+            addend.conditional_assign(point, is_zero);
+            let sum = &result + &addend;
+            result.conditional_assign(&sum, !is_zero);
+        }
+
+        result + -offset
+    }
+
     /// Binary expansion method for `BigUint`
     pub fn binary_expansion_biguint(&self, coefficient: BigUint) -> Self {
         let mut coefficient = coefficient;
@@ -162,67 +364,137 @@ impl Point {
     }
 }
 
-impl Add for Point {
-    type Output = Self;
-
+impl Point {
     /// Performs point addition on two points on an elliptic curve
     /// <https://en.wikipedia.org/wiki/Elliptic_curve_point_multiplication#Point_addition>
     ///
     /// In a high level, given points P1(x1,y1) and P2(x2,y2) on an elliptic curve, adding P1 and P2
     /// means drawing a line through P1 and P2 and finding the point where the line intersects the
     /// curve and taking its reflection across the x-axis.
+    ///
+    /// Works on references so that neither point has to be cloned, the `Add` impls for
+    /// references use this.
+    ///
+    /// # Panics
+    ///
+    /// Panics if only one of the coordinates of a point is infinity
     #[allow(clippy::erasing_op)]
+    pub fn add_ref(&self, rhs: &Self) -> Self {
+        // Case 0.0: self points to Infinity, return rhs
+        // This is the identity element for addition
+        if self.is_identity() {
+            return rhs.clone();
+        }
+        // Case 0.1: rhs points to Infinity, return self
+        // This is the identity element for addition
+        if rhs.is_identity() {
+            return self.clone();
+        }
+
+        let (
+            PointType::Normal(x1),
+            PointType::Normal(y1),
+            PointType::Normal(x2),
+            PointType::Normal(y2),
+        ) = (&self.x, &self.y, &rhs.x, &rhs.y)
+        else {
+            panic!("Cannot unwrap infinity")
+        };
+        let curve = self.curve.clone();
+
+        if x1 == x2 && y1 != y2 {
+            // Case 1: self.x == rhs.x && self.y != rhs.y; return Infinity
+            // If we are on the same x but different y, we are tangent to the vertical line
+            Self::new(PointType::Infinity, PointType::Infinity, curve)
+        } else if x1 != x2 {
+            // Case 2: self.x != rhs.x
+            // Formula (x3,y3) == (x1,y1) + (x2,y2)
+            //  s = (y2-y1) / (x2-x1)
+            // x3 = s ** 2 - x1 - x2
+            // y3 = s * (x1-x3) - y1
+            let s = (y2 - y1) / (x2 - x1);
+            let x3 = s.pow(2u32) - x1 - x2;
+            let y3 = s * (x1 - &x3) - y1;
+
+            Self::new(PointType::Normal(x3), PointType::Normal(y3), curve)
+        } else if y1 == &(x1 * 0u32) {
+            // Case 4: if we are tangent to the vertical line, we return the point at infinity
+            // note instead of figuring out what 0 is for each type we just use 0 * self.x
+            Self::new(PointType::Infinity, PointType::Infinity, curve)
+        } else {
+            // Case 3: self == other
+            // Formula (x3,y3)=(x1,y1)+(x1,y1)
+            //  s = (3 * x1**2 + a) / (2 * y1)
+            // x3 = s**2 - 2 * x1
+            // y3 = s * (x1-x3) - y1
+            let s = (&x1.pow(2u32) * 3u32 + &curve.a) / (y1 * 2u32);
+            let x3 = &s.pow(2u32) - x1 * 2u32;
+            let y3 = &s * (x1 - &x3) - y1;
+            Self::new(PointType::Normal(x3), PointType::Normal(y3), curve)
+        }
+    }
+}
+
+impl Neg for &Point {
+    type Output = Point;
+
+    /// Returns `-P`, the point with the same x and `y = -y`
+    fn neg(self) -> Self::Output {
+        match &self.y {
+            PointType::Infinity => self.clone(),
+            PointType::Normal(y) => {
+                let y = Felt::new(BigUint::from(0u32), y.prime().clone()) - y;
+                Point { x: self.x.clone(), y: PointType::Normal(y), curve: self.curve.clone() }
+            }
+        }
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Self::Output {
+        -&self
+    }
+}
+
+impl Add for Point {
+    type Output = Self;
+
+    /// Adds two points, see `Point::add_ref`
     fn add(self, rhs: Self) -> Self::Output {
-        match &self.x {
-            PointType::Normal(x1) => match &rhs.x {
-                PointType::Normal(x2) => {
-                    let y1 = self.y.unwrap();
-                    let y2 = rhs.y.unwrap();
-
-                    if x1 == x2 && y1 != y2 {
-                        // Case 1: self.x == rhs.x && self.y != rhs.y; return Infinity
-                        // If we are on the same x but different y, we are tangent to the vertical line
-                        Self::new(PointType::Infinity, PointType::Infinity, self.curve)
-                    } else if x1 != x2 {
-                        // Case 2: self.x != rhs.x
-                        // Formula (x3,y3) == (x1,y1) + (x2,y2)
-                        //  s = (y2-y1) / (x2-x1)
-                        // x3 = s ** 2 - x1 - x2
-                        // y3 = s * (x1-x3) - y1
-                        let s = (&y2 - &y1) / (x2 - x1);
-                        let x3 = s.pow(2u32) - x1 - x2;
-                        let y3 = s * (x1 - &x3) - &y1;
-
-                        Self::new(PointType::Normal(x3), PointType::Normal(y3), self.curve)
-                    } else if y1 == x1 * 0u32 {
-                        // Case 4: if we are tangent to the vertical line, we return the point at infinity
-                        // note instead of figuring out what 0 is for each type we just use 0 * self.x
-                        Self::new(PointType::Infinity, PointType::Infinity, self.curve)
-                    } else {
-                        // Case 3: self == other
-                        // Formula (x3,y3)=(x1,y1)+(x1,y1)
-                        //  s = (3 * x1**2 + a) / (2 * y1)
-                        // x3 = s**2 - 2 * x1
-                        // y3 = s * (x1-x3) - y1
-                        let curve = &self.curve;
-                        let s = (&x1.pow(2u32) * 3u32 + &curve.a) / (&y1 * 2u32);
-                        let x3 = &s.pow(2u32) - x1 * 2u32;
-                        let y3 = &s * (x1 - &x3) - &y1;
-                        Self::new(PointType::Normal(x3), PointType::Normal(y3), self.curve)
-                    }
-                }
-                // Case 0.1: rhs points to Infinity, return self
-                // This is the identity element for addition
-                PointType::Infinity => self,
-            },
-            // Case 0.0: self points to Infinity, return rhs
-            // This is the identity element for addition
-            PointType::Infinity => rhs,
+        // Owned operands can be returned as they are for the identity
+        match (&self.x, &rhs.x) {
+            (PointType::Infinity, _) => rhs,
+            (_, PointType::Infinity) => self,
+            _ => self.add_ref(&rhs),
         }
     }
 }
 
-impl_refs!(Add, add, Point, Point);
+impl Add<&Point> for &Point {
+    type Output = Point;
+
+    fn add(self, rhs: &Point) -> Self::Output {
+        self.add_ref(rhs)
+    }
+}
+
+impl Add<&Point> for Point {
+    type Output = Point;
+
+    fn add(self, rhs: &Point) -> Self::Output {
+        self.add_ref(rhs)
+    }
+}
+
+impl Add<Point> for &Point {
+    type Output = Point;
+
+    fn add(self, rhs: Point) -> Self::Output {
+        self.add_ref(&rhs)
+    }
+}
 
 impl Mul<u32> for Point {
     type Output = Self;
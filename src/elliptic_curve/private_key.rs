@@ -1,55 +1,232 @@
-use crate::helpers::hash::hash256;
+use crate::{
+    helpers::{base58, bech32, bytes::to_32_bytes_be, hash::hash256},
+    taproot,
+};
 
 use super::{
-    secp256k1::{Secp256k1Felt, Secp256k1Point},
+    point::Point,
+    public_key::{Network, PublicKey},
+    scalar::Scalar,
+    secp256k1::Secp256k1Point,
     signature::{self, Signature},
 };
-use hmac::{Hmac, Mac};
+use hmac::{
+    digest::{core_api::BlockSizeUser, Digest},
+    Mac, SimpleHmac,
+};
 use num_bigint::BigUint;
+use rand::{CryptoRng, Rng, RngCore};
 use sha2::Sha256;
-use std::io::Read;
+use std::{fmt::Display, io::Read};
+
+/// Reasons a WIF private key can fail to import
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WifError {
+    /// Not Base58 or the checksum doesn't match
+    InvalidBase58,
+    InvalidLength(usize),
+    UnknownVersion(u8),
+    InvalidCompressionFlag(u8),
+    /// The secret is zero or not below the group order
+    InvalidSecret,
+    NetworkMismatch { expected: Network, actual: Network },
+}
+
+impl Display for WifError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidBase58 => write!(f, "WIF is not valid checksummed Base58"),
+            Self::InvalidLength(length) => write!(f, "Invalid WIF payload length {length}"),
+            Self::UnknownVersion(version) => write!(f, "Unknown WIF version byte {version:#04x}"),
+            Self::InvalidCompressionFlag(flag) => {
+                write!(f, "Invalid WIF compression flag {flag:#04x}")
+            }
+            Self::InvalidSecret => write!(f, "WIF secret is not a valid private key"),
+            Self::NetworkMismatch { expected, actual } => {
+                write!(f, "WIF is for {actual:?} but {expected:?} was expected")
+            }
+        }
+    }
+}
+
+impl std::error::Error for WifError {}
+
+/// Everything derived from a key's public half that key inspection tools print
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyInfo {
+    pub compressed_sec: Vec<u8>,
+    pub uncompressed_sec: Vec<u8>,
+    /// hash160 of the compressed SEC
+    pub hash160: [u8; 20],
+    /// P2PKH address of the compressed key
+    pub p2pkh: String,
+    pub p2wpkh: String,
+}
 
 #[derive(Debug, Clone)]
 pub struct PrivateKey {
-    secret: Secp256k1Felt,
+    secret: Scalar,
     public_key: Secp256k1Point,
 }
 
-type Hmac256 = Hmac<Sha256>;
-
 impl PrivateKey {
-    /// Creates a new private key from a field element
-    pub fn new(secret: Secp256k1Felt) -> Self {
-        let public_key = Secp256k1Point::g() * secret.inner();
+    /// Creates a new private key from a scalar
+    pub fn new(secret: Scalar) -> Self {
+        let public_key = Secp256k1Point::mul_generator(secret.inner());
         Self { secret, public_key }
     }
 
+    /// Generates a new random private key
+    ///
+    /// 32 random bytes are drawn until they are a scalar in `[1, n - 1]`, so the key is
+    /// uniform over all valid keys. Since n is close to 2^256 a retry almost never happens.
+    /// The `CryptoRng` bound keeps non cryptographic generators out.
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let n = Secp256k1Point::order();
+        loop {
+            let mut bytes = [0u8; 32];
+            rng.fill_bytes(&mut bytes);
+
+            let candidate = BigUint::from_bytes_be(&bytes);
+            if candidate > BigUint::from(0u32) && candidate < n {
+                return Self::new(Scalar::new(candidate));
+            }
+        }
+    }
+
+    /// Derives a private key from a small integer, for tests and examples
+    ///
+    /// The key is `SHA256(seed)` reduced modulo the group order, rehashing in the
+    /// practically impossible case that this is zero. Anyone can enumerate small seeds,
+    /// so these keys must never hold real funds; use `generate` for that.
+    pub fn from_seed_u64(seed: u64) -> Self {
+        let mut hash = Sha256::digest(seed.to_le_bytes());
+        loop {
+            let secret = Scalar::from_bytes(&hash);
+            if !secret.is_zero() {
+                return Self::new(secret);
+            }
+            hash = Sha256::digest(hash);
+        }
+    }
+
+    /// Returns the public key of the private key
+    pub fn public_key(&self) -> &Secp256k1Point {
+        &self.public_key
+    }
+
+    /// Returns the encodings, hash and addresses of the public key on the network
+    pub fn key_info(&self, network: Network) -> KeyInfo {
+        let public_key = PublicKey::new(self.public_key.clone(), network);
+        KeyInfo {
+            compressed_sec: self.public_key.to_sec(true),
+            uncompressed_sec: self.public_key.to_sec(false),
+            hash160: public_key.identifier(),
+            p2pkh: public_key.p2pkh_address(true),
+            p2wpkh: public_key.p2wpkh_address(),
+        }
+    }
+
+    /// Returns the key path only P2TR address of the key on the network
+    ///
+    /// The public key is the internal key, tweaked without a script tree, and the x-only
+    /// output key is the version 1 witness program.
+    ///
+    /// # Panics
+    ///
+    /// Doesn't panic in practice, the tweak fails with negligible probability
+    pub fn p2tr_address(&self, network: Network) -> String {
+        let (output_key, _) = taproot::tweak_public_key(&self.public_key, None).unwrap();
+        bech32::encode_segwit(network.bech32_hrp(), 1, &output_key).unwrap()
+    }
+
+    pub(crate) fn secret(&self) -> &Scalar {
+        &self.secret
+    }
+
     /// Signs a field element using the private key
     ///
     /// # Panics
     ///
     /// This method will panic if the field element is not a valid field element
-    #[allow(clippy::many_single_char_names)]
-    pub fn sign(&self, z: &Secp256k1Felt) -> Signature {
-        let k = self.deterministic_k(z.inner().clone());
+    pub fn sign(&self, z: &Scalar) -> Signature {
+        self.sign_recoverable(z).0
+    }
 
-        // r = (k * G).x
-        let g_x = (Secp256k1Point::g() * &k).x().clone().unwrap();
-        let r = Secp256k1Felt::from(g_x);
+    /// Signs a field element like `sign`, also returning the recovery id
+    ///
+    /// The recovery id is the parity of `R.y` in its lowest bit and whether `R.x` was
+    /// at least the group order in the next, see `Signature::recover`. It is taken from
+    /// the nonce point while signing instead of trying all four ids afterwards.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the field element is not a valid field element
+    pub fn sign_recoverable(&self, z: &Scalar) -> (Signature, u8) {
+        let k = self.deterministic_k(z.inner().clone());
 
-        // s = (z + r * secret) / k
-        let k = Secp256k1Felt::new(k);
-        let mut s = (z + &r * &self.secret) / &k;
+        // R = k * G
+        let nonce_point: Point = Secp256k1Point::mul_generator(&k).into();
+        self.sign_with_nonce(z, k, &nonce_point)
+    }
 
-        // if s > n / 2 then s = n - s
+    /// Signs a field element like `sign`, but blinds the `k * G` multiplication
+    ///
+    /// The nonce point is computed as `k' * (G + B) - k' * B` with `k' = k + r * n` and
+    /// `B = b * G` for random `r` and `b`. Since `n * G` is the identity this is `k * G`
+    /// and the signature is identical to the one `sign` produces, but neither
+    /// multiplication by the nonce is done on the generator or with the nonce itself,
+    /// and both change on every call. This makes it harder to recover the nonce, and with
+    /// it the secret, through timing or power side channels.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if the field element is not a valid field element
+    pub fn sign_blinded(&self, z: &Scalar) -> Signature {
+        let k = self.deterministic_k(z.inner().clone());
         let n = Secp256k1Point::order();
-        let n_half = &n / BigUint::from(2u32);
 
-        if s.inner() > &n_half {
-            s = Secp256k1Felt::new(n - s.inner());
+        let mut rng = rand::thread_rng();
+        let scalar_blind = BigUint::from(rng.gen::<u64>());
+        let point_blind = BigUint::from_bytes_be(&rng.gen::<[u8; 32]>()) % &n;
+
+        // Multiply the underlying point, Secp256k1Point would reduce the blinded scalar
+        let g: Point = Secp256k1Point::g().into();
+        let blind_point = g.binary_expansion_biguint(point_blind);
+        let blinded_k = &k + scalar_blind * &n;
+        let blinded = (&g + &blind_point).binary_expansion_biguint(blinded_k.clone())
+            + -&blind_point.binary_expansion_biguint(blinded_k);
+
+        self.sign_with_nonce(z, k, &blinded).0
+    }
+
+    /// Finishes a signature given the nonce and the nonce point `R = k * G`
+    ///
+    /// Returns the recovery id of the signature with it
+    #[allow(clippy::many_single_char_names)]
+    fn sign_with_nonce(&self, z: &Scalar, k: BigUint, nonce_point: &Point) -> (Signature, u8) {
+        let r_x = nonce_point.x.clone().unwrap();
+        let r_y = nonce_point.y.clone().unwrap();
+        let mut recovery_id = u8::from(r_y.inner().bit(0));
+        if r_x.inner() >= &Secp256k1Point::order() {
+            recovery_id |= 2;
         }
 
-        Signature::new(r, s)
+        // r = R.x mod n
+        let r = Scalar::new(r_x.to_biguint());
+
+        // s = (z + r * secret) / k
+        let k = Scalar::new(k);
+        let s = (z + &r * &self.secret) / &k;
+
+        // if s > n / 2 then s = n - s, which is the signature for -R, flipping the parity
+        let signature = Signature::new(r, s);
+        if signature.is_low_s() {
+            (signature, recovery_id)
+        } else {
+            let s = -signature.s();
+            (Signature::new(signature.r().clone(), s), recovery_id ^ 1)
+        }
     }
 
     /// Signs a byte slice using the private key
@@ -60,84 +237,172 @@ impl PrivateKey {
     /// This method will panic if the hash of the message is not a valid field element
     pub fn sign_slice(&self, message: &[u8]) -> Signature {
         let hash = hash256(message);
-        let z = Secp256k1Felt::from_bytes(hash.as_slice());
+        let z = Scalar::from_bytes(hash.as_slice());
 
         self.sign(&z)
     }
 
     /// Checks if message is signed by this private key
-    pub fn verify(&self, z: &Secp256k1Felt, sig: &Signature) -> bool {
+    pub fn verify(&self, z: &Scalar, sig: &Signature) -> bool {
         sig.verify(z, &self.public_key)
     }
 
     /// Convenience method to verify a slice after hashing it
     pub fn verify_slice(&self, message: &[u8], sig: &Signature) -> bool {
         let message_digest = hash256(message);
-        let z = Secp256k1Felt::from_bytes(&message_digest);
+        let z = Scalar::from_bytes(&message_digest);
         self.verify(&z, sig)
     }
 
+    /// Encodes the key in Wallet Import Format
+    ///
+    /// Base58 with checksum of the network's version byte and the 32 byte secret, followed by
+    /// `0x01` when the key's addresses use the compressed public key.
+    pub fn to_wif(&self, network: Network, compressed: bool) -> String {
+        let mut payload = vec![network.wif_prefix()];
+        payload.extend_from_slice(&to_32_bytes_be(self.secret.inner()));
+        if compressed {
+            payload.push(0x01);
+        }
+        base58::encode_check(&payload)
+    }
+
+    /// Decodes a key in Wallet Import Format
+    ///
+    /// Returns the key, the network of its version byte and whether it is compressed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string is not valid checksummed Base58, the version byte or
+    /// compression flag is unknown, or the secret is not a valid private key
+    pub fn from_wif(s: &str) -> Result<(Self, Network, bool), WifError> {
+        let payload = base58::decode_check(s).map_err(|_| WifError::InvalidBase58)?;
+        let (secret, compressed) = match payload.len() {
+            33 => (&payload[1..], false),
+            34 if payload[33] == 0x01 => (&payload[1..33], true),
+            34 => return Err(WifError::InvalidCompressionFlag(payload[33])),
+            length => return Err(WifError::InvalidLength(length)),
+        };
+        let network =
+            Network::from_wif_prefix(payload[0]).ok_or(WifError::UnknownVersion(payload[0]))?;
+
+        let secret = BigUint::from_bytes_be(secret);
+        if secret == BigUint::from(0u32) || secret >= Secp256k1Point::order() {
+            return Err(WifError::InvalidSecret);
+        }
+
+        Ok((Self::new(Scalar::new(secret)), network, compressed))
+    }
+
+    /// Decodes a key in Wallet Import Format, checking it is for the expected network
+    ///
+    /// Importing a testnet key into a mainnet wallet, or the other way around, is almost
+    /// always a mistake. Returns the key and whether it is compressed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `WifError::NetworkMismatch` if the version byte is for another network,
+    /// or any error `from_wif` returns
+    pub fn from_wif_for_network(s: &str, expected: Network) -> Result<(Self, bool), WifError> {
+        let (private_key, actual, compressed) = Self::from_wif(s)?;
+        if actual != expected {
+            return Err(WifError::NetworkMismatch { expected, actual });
+        }
+        Ok((private_key, compressed))
+    }
+
     /// Creates a unique, deterministic k value
     ///
     /// This is important because if the same k value is used twice, the private key can
     /// be recovered using both signatures.
     ///
     /// The specification for determining k is defined in RFC 6779 (<https://tools.ietf.org/html/rfc6979>)
-    fn deterministic_k(&self, z: BigUint) -> BigUint {
-        let k = [0u8; 32];
-        let v = [1u8; 32];
+    pub fn deterministic_k(&self, z: BigUint) -> BigUint {
+        self.deterministic_k_with::<Sha256>(z)
+    }
+
+    /// Creates a deterministic k value like `deterministic_k`, using `D` for the HMAC
+    ///
+    /// RFC 6979 works with any hash function. Bitcoin uses SHA-256, which is what
+    /// `deterministic_k` picks, but other protocols on secp256k1 may sign with another one.
+    ///
+    /// # Panics
+    ///
+    /// Doesn't panic in practice, HMAC accepts keys of any length
+    pub fn deterministic_k_with<D>(&self, z: BigUint) -> BigUint
+    where
+        D: Digest + BlockSizeUser,
+    {
+        self.deterministic_k_extra::<D>(z, &[])
+    }
+
+    /// Creates a deterministic k value like `deterministic_k`, mixing in a counter
+    ///
+    /// The counter is passed as RFC 6979 extra data, 32 bytes with the counter little
+    /// endian at the front, the way Bitcoin Core grinds for low R signatures. Counter 0
+    /// adds no extra data and gives the same k as `deterministic_k`. Each counter gives
+    /// a different k for the same message, and reusing a counter reuses the k. That is
+    /// fine, the same message and counter sign to the same signature, and the message
+    /// is mixed in too, so one counter across different messages still gives unrelated k.
+    pub fn deterministic_k_with_counter(&self, z: BigUint, counter: u32) -> BigUint {
+        if counter == 0 {
+            return self.deterministic_k(z);
+        }
+
+        let mut extra_data = [0u8; 32];
+        extra_data[..4].copy_from_slice(&counter.to_le_bytes());
+        self.deterministic_k_extra::<Sha256>(z, &extra_data)
+    }
+
+    /// RFC 6979 with `extra_data` appended to the secret and hash, section 3.6
+    fn deterministic_k_extra<D>(&self, z: BigUint, extra_data: &[u8]) -> BigUint
+    where
+        D: Digest + BlockSizeUser,
+    {
+        let hmac = |key: &[u8], parts: &[&[u8]]| {
+            let mut hmac = SimpleHmac::<D>::new_from_slice(key).unwrap();
+            for part in parts {
+                hmac.update(part);
+            }
+            hmac.finalize().into_bytes().to_vec()
+        };
+
+        let hash_len = <D as Digest>::output_size();
+        let k = vec![0u8; hash_len];
+        let v = vec![1u8; hash_len];
 
         let mut z = z;
         if z > Secp256k1Point::order() {
             z -= Secp256k1Point::order();
         }
 
-        let z = z.to_bytes_be();
-        let mut z_bytes = vec![0; 32 - z.len()];
-        z_bytes.extend_from_slice(z.as_slice());
+        let z_bytes = to_32_bytes_be(&z);
 
         // Pad secret to 32 bytes, from the left
-        let secret = self.secret.inner().to_bytes_be();
-        let mut secret_bytes = vec![0; 32 - secret.len()];
-        secret_bytes.extend_from_slice(secret.as_slice());
-
-        // k := hmac_k (v || 0x00 || secret_bytes || z_bytes)
-        let mut hmac = Hmac256::new_from_slice(&k).unwrap();
-        let mut data = Vec::new();
-        data.extend_from_slice(&v);
-        data.push(0);
-        data.extend_from_slice(&secret_bytes);
-        data.extend_from_slice(&z_bytes);
-        hmac.update(&data);
-        let k = hmac.finalize().into_bytes();
+        let secret_bytes = to_32_bytes_be(self.secret.inner());
+
+        // k := hmac_k (v || 0x00 || secret_bytes || z_bytes || extra_data)
+        let k = hmac(&k, &[&v, &[0], &secret_bytes, &z_bytes, extra_data]);
 
         // v := hmac_k (v)
-        let mut hmac = Hmac256::new_from_slice(&k).unwrap();
-        hmac.update(&v);
-        let v = hmac.finalize().into_bytes();
-
-        // k := hmac_k (v || 0x01 || secret_bytes || z_bytes)
-        let mut hmac = Hmac256::new_from_slice(&k).unwrap();
-        let mut data = Vec::new();
-        data.extend_from_slice(&v);
-        data.push(1);
-        data.extend_from_slice(&secret_bytes);
-        data.extend_from_slice(&z_bytes);
-        hmac.update(&data);
-        let mut k = hmac.finalize().into_bytes();
+        let v = hmac(&k, &[&v]);
+
+        // k := hmac_k (v || 0x01 || secret_bytes || z_bytes || extra_data)
+        let mut k = hmac(&k, &[&v, &[1], &secret_bytes, &z_bytes, extra_data]);
 
         // v := hmac_k (v)
-        let mut hmac = Hmac256::new_from_slice(&k).unwrap();
-        hmac.update(&v);
-        let mut v = hmac.finalize().into_bytes();
+        let mut v = hmac(&k, &[&v]);
 
         loop {
-            // v := hmac_k (v)
-            let mut hmac = Hmac256::new_from_slice(&k).unwrap();
-            hmac.update(&v);
-            v = hmac.finalize().into_bytes();
+            // t := v || hmac_k (v) || ... until it has at least 32 bytes
+            let mut t = Vec::with_capacity(32);
+            while t.len() < 32 {
+                // v := hmac_k (v)
+                v = hmac(&k, &[&v]);
+                t.extend_from_slice(&v);
+            }
 
-            let candidate = BigUint::from_bytes_be(&v);
+            let candidate = BigUint::from_bytes_be(&t[..32]);
             let one = BigUint::from(1u8);
 
             if candidate > one && candidate < Secp256k1Point::order() {
@@ -145,17 +410,10 @@ impl PrivateKey {
             }
 
             // k := hmac_k (v || 0x00)
-            let mut hmac = Hmac256::new_from_slice(&k).unwrap();
-            let mut data = Vec::new();
-            data.extend_from_slice(&v);
-            data.push(0);
-            hmac.update(&data);
-            k = hmac.finalize().into_bytes();
+            k = hmac(&k, &[&v, &[0]]);
 
             // v := hmac_k (v)
-            let mut hmac = Hmac256::new_from_slice(&k).unwrap();
-            hmac.update(&v);
-            v = hmac.finalize().into_bytes();
+            v = hmac(&k, &[&v]);
         }
     }
 }
@@ -0,0 +1,135 @@
+use super::secp256k1::Secp256k1Point;
+use crate::helpers::{
+    base58, bech32,
+    hash::{Hasher, StandardHasher},
+};
+
+/// Bitcoin network a key is used on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+}
+
+impl Network {
+    /// Version byte of P2PKH addresses on the network
+    pub fn p2pkh_prefix(self) -> u8 {
+        match self {
+            Self::Mainnet => 0x00,
+            Self::Testnet => 0x6f,
+        }
+    }
+
+    /// Human readable part of segwit addresses on the network
+    pub fn bech32_hrp(self) -> &'static str {
+        match self {
+            Self::Mainnet => "bc",
+            Self::Testnet => "tb",
+        }
+    }
+
+    /// Version byte of WIF private keys on the network
+    pub fn wif_prefix(self) -> u8 {
+        match self {
+            Self::Mainnet => 0x80,
+            Self::Testnet => 0xef,
+        }
+    }
+
+    /// Returns the network a WIF version byte belongs to
+    pub fn from_wif_prefix(prefix: u8) -> Option<Self> {
+        [Self::Mainnet, Self::Testnet]
+            .into_iter()
+            .find(|network| network.wif_prefix() == prefix)
+    }
+}
+
+/// A public key on SECP256K1 curve, together with the network it belongs to
+///
+/// Keeping the network next to the key means addresses are always derived with the
+/// right version byte, instead of trusting every caller to pass it along.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublicKey {
+    point: Secp256k1Point,
+    network: Network,
+}
+
+impl PublicKey {
+    /// Creates a new public key on the given network
+    pub fn new(point: Secp256k1Point, network: Network) -> Self {
+        Self { point, network }
+    }
+
+    /// Returns the point of the public key
+    pub fn point(&self) -> &Secp256k1Point {
+        &self.point
+    }
+
+    /// Returns the network of the public key
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// Returns the hash160 of the compressed key
+    ///
+    /// BIP32 uses this as the key identifier
+    pub fn identifier(&self) -> [u8; 20] {
+        self.identifier_with(&StandardHasher)
+    }
+
+    /// Returns the identifier, hashing with the given hasher
+    pub fn identifier_with(&self, hasher: &impl Hasher) -> [u8; 20] {
+        hasher.hash160(&self.point.to_sec(true))
+    }
+
+    /// Returns the first four bytes of the identifier
+    ///
+    /// BIP32 stores this as the parent fingerprint of child keys
+    pub fn fingerprint(&self) -> [u8; 4] {
+        let identifier = self.identifier();
+        [identifier[0], identifier[1], identifier[2], identifier[3]]
+    }
+
+    /// Returns the P2PKH address of the compressed key on the key's network
+    pub fn address(&self) -> String {
+        self.p2pkh_address(true)
+    }
+
+    /// Returns the P2PKH address of the key on the key's network
+    ///
+    /// The address commits to one SEC encoding, so the compressed and uncompressed
+    /// forms of the same key have different addresses.
+    pub fn p2pkh_address(&self, compressed: bool) -> String {
+        self.p2pkh_address_with(&StandardHasher, compressed)
+    }
+
+    /// Returns the P2WPKH address of the key on the key's network
+    ///
+    /// Segwit only allows compressed keys, so the program is the key's identifier.
+    ///
+    /// # Panics
+    ///
+    /// Doesn't panic in practice, a 20 byte program is always valid for version 0
+    pub fn p2wpkh_address(&self) -> String {
+        self.p2wpkh_address_with(&StandardHasher)
+    }
+
+    /// Returns the P2WPKH address of the key, hashing with the given hasher
+    ///
+    /// # Panics
+    ///
+    /// Doesn't panic in practice, a 20 byte program is always valid for version 0
+    pub fn p2wpkh_address_with(&self, hasher: &impl Hasher) -> String {
+        let program = self.identifier_with(hasher);
+        bech32::encode_segwit(self.network.bech32_hrp(), 0, &program).unwrap()
+    }
+
+    /// Returns the P2PKH address of the key, hashing with the given hasher
+    ///
+    /// Both the key hash and the Base58 checksum come from the hasher.
+    pub fn p2pkh_address_with(&self, hasher: &impl Hasher, compressed: bool) -> String {
+        let mut payload = vec![self.network.p2pkh_prefix()];
+        payload.extend_from_slice(&hasher.hash160(&self.point.to_sec(compressed)));
+        base58::encode_check_with(hasher, &payload)
+    }
+}
@@ -0,0 +1,150 @@
+use super::secp256k1::Secp256k1Point;
+use crate::{
+    finite_fields::{macros::impl_refs, modulo::Modulo},
+    helpers::bytes::to_32_bytes_be,
+};
+use num_bigint::BigUint;
+use primitive_types::U256;
+use std::{
+    fmt::Display,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+/// An integer modulo the order of SECP256K1's generator
+///
+/// Coordinates live in the field of the prime `p`, but private keys, nonces and
+/// signature values are multiplied with points, so their arithmetic is modulo the
+/// group order `n`. Keeping them in a separate type from `Secp256k1Felt` means the two
+/// moduli can't be mixed up by accident.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Scalar(BigUint);
+
+impl Scalar {
+    /// Creates a new scalar, reducing the value modulo the group order
+    pub fn new(value: BigUint) -> Self {
+        Self(value % Secp256k1Point::order())
+    }
+
+    /// Creates a new scalar from a big endian integer, reducing it modulo the group order
+    pub fn from_bytes(value: &[u8]) -> Self {
+        Self::new(BigUint::from_bytes_be(value))
+    }
+
+    /// Returns the value of the scalar
+    pub fn inner(&self) -> &BigUint {
+        &self.0
+    }
+
+    /// Serializes the scalar as a 32 byte big endian integer
+    pub fn to_bytes(&self) -> [u8; 32] {
+        to_32_bytes_be(&self.0)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == BigUint::from(0u32)
+    }
+
+    /// Returns the multiplicative inverse of the scalar, or `None` for zero
+    ///
+    /// The group order is prime, so by Fermat's little theorem `a^-1 = a^(n-2) (mod n)`
+    pub fn inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+
+        let n = Secp256k1Point::order();
+        let exponent = &n - BigUint::from(2u32);
+        Some(Self(self.0.modpow(&exponent, &n)))
+    }
+}
+
+/// Sums 256 bit integers modulo the group order
+///
+/// The running sum is kept as a `Scalar`, so it is reduced after every addition and
+/// can't overflow no matter how many values there are or how large they are.
+pub fn sum_mod_order(scalars: &[U256]) -> U256 {
+    let sum = scalars.iter().fold(Scalar::default(), |sum, value| {
+        let mut bytes = [0u8; 32];
+        value.to_big_endian(&mut bytes);
+        sum + Scalar::from_bytes(&bytes)
+    });
+    U256::from_big_endian(&sum.to_bytes())
+}
+
+impl From<u32> for Scalar {
+    fn from(value: u32) -> Self {
+        Self::new(BigUint::from(value))
+    }
+}
+
+impl Display for Scalar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0x{value:0>64}", value = self.0.to_str_radix(16))
+    }
+}
+
+impl Add for Scalar {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self((self.0 + rhs.0).modulo(&Secp256k1Point::order()))
+    }
+}
+
+impl_refs!(Add, add, Scalar, Scalar);
+
+impl Sub for Scalar {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl_refs!(Sub, sub, Scalar, Scalar);
+
+impl Mul for Scalar {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self((self.0 * rhs.0).modulo(&Secp256k1Point::order()))
+    }
+}
+
+impl_refs!(Mul, mul, Scalar, Scalar);
+
+impl Div for Scalar {
+    type Output = Self;
+
+    /// Multiplies by the inverse of `rhs`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rhs` is zero
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Self) -> Self::Output {
+        self * rhs.inverse().expect("Division by zero scalar")
+    }
+}
+
+impl_refs!(Div, div, Scalar, Scalar);
+
+impl Neg for Scalar {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        if self.is_zero() {
+            self
+        } else {
+            Self(Secp256k1Point::order() - self.0)
+        }
+    }
+}
+
+impl Neg for &Scalar {
+    type Output = Scalar;
+
+    fn neg(self) -> Self::Output {
+        -self.clone()
+    }
+}
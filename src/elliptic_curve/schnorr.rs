@@ -0,0 +1,48 @@
+use super::{scalar::Scalar, secp256k1::Secp256k1Point};
+use crate::helpers::hash::BIP0340_CHALLENGE;
+use num_bigint::BigUint;
+
+/// Returns whether the bytes are a valid BIP340 x-only public key
+///
+/// An x-only key stands for the point with that x and an even y, so it is only valid
+/// when x is in the field and `x^3 + 7` has a square root.
+pub fn is_valid_xonly(x: &[u8; 32]) -> bool {
+    Secp256k1Point::lift_x(&BigUint::from_bytes_be(x), false).is_ok()
+}
+
+/// Parses a BIP340 signature into the parts of its verification equation
+///
+/// Returns `(R, e, P)` where `R` is the nonce point lifted from the signature's r, `e`
+/// is the challenge `hash_BIP0340/challenge(r || P.x || message)` and `P` is the public
+/// key lifted from its x-only form. The signature is valid exactly when `s * G` equals
+/// `R + e * P`, so a batch verifier can combine many of these without parsing again.
+///
+/// Returns `None` if the key or r are not x coordinates of points with an even y, or s
+/// is not below the group order.
+pub fn verify_components(
+    pubkey_x: &[u8; 32],
+    message: &[u8],
+    sig: &[u8; 64],
+) -> Option<(Secp256k1Point, Scalar, Secp256k1Point)> {
+    let public_key = Secp256k1Point::lift_x(&BigUint::from_bytes_be(pubkey_x), false).ok()?;
+    let nonce_point = Secp256k1Point::lift_x(&BigUint::from_bytes_be(&sig[..32]), false).ok()?;
+    if BigUint::from_bytes_be(&sig[32..]) >= Secp256k1Point::order() {
+        return None;
+    }
+
+    let mut challenge = Vec::with_capacity(64 + message.len());
+    challenge.extend_from_slice(&sig[..32]);
+    challenge.extend_from_slice(pubkey_x);
+    challenge.extend_from_slice(message);
+    let e = Scalar::from_bytes(&BIP0340_CHALLENGE.hash(&challenge));
+
+    Some((nonce_point, e, public_key))
+}
+
+/// Verifies a BIP340 signature, checking `s * G == R + e * P`, see `verify_components`
+pub fn verify(pubkey_x: &[u8; 32], message: &[u8], sig: &[u8; 64]) -> bool {
+    verify_components(pubkey_x, message, sig).is_some_and(|(nonce_point, e, public_key)| {
+        Secp256k1Point::mul_generator(&BigUint::from_bytes_be(&sig[32..]))
+            == nonce_point + public_key * e.inner()
+    })
+}
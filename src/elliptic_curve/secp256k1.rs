@@ -1,16 +1,48 @@
 use super::{
     curve::Curve,
     point::{Point, PointType},
+    scalar::Scalar,
     signature::Signature,
 };
-use crate::finite_fields::{element::Felt, macros::impl_refs, modulo::Modulo};
+use crate::{
+    finite_fields::{element::Felt, macros::impl_refs, modulo::Modulo},
+    helpers::{bytes::to_32_bytes_be, hex, reader::Reader},
+};
 use color_eyre::eyre::{eyre, Result};
 use num_bigint::BigUint;
+use primitive_types::U256;
+use sha2::{Digest, Sha256};
 use std::{
     fmt::Display,
     ops::{Add, Div, Mul},
+    sync::LazyLock,
 };
 
+// Curve parameters are parsed once and reused, `BigUint` can't be built in a const
+static PRIME: LazyLock<BigUint> = LazyLock::new(|| {
+    BigUint::parse_bytes(Secp256k1Felt::SECP256K1_PRIME, 16).unwrap_or_default()
+});
+
+static ORDER: LazyLock<BigUint> = LazyLock::new(|| {
+    BigUint::parse_bytes(Secp256k1Felt::SECP256K1_ORDER, 16).unwrap_or_default()
+});
+
+static CURVE: LazyLock<Curve> = LazyLock::new(|| {
+    Curve::new(
+        Secp256k1Felt::new(BigUint::from(0u8)).into(),
+        Secp256k1Felt::new(BigUint::from(7u8)).into(),
+    )
+});
+
+static GENERATOR: LazyLock<Secp256k1Point> = LazyLock::new(|| {
+    Secp256k1Point::new(
+        BigUint::parse_bytes(Secp256k1Point::SECP256K1_X, 16).unwrap_or_default(),
+        BigUint::parse_bytes(Secp256k1Point::SECP256K1_Y, 16).unwrap_or_default(),
+    )
+});
+
+static GENERATOR_TABLE: LazyLock<Vec<Point>> = LazyLock::new(|| GENERATOR.0.window_table());
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Secp256k1Felt(Felt);
 
@@ -21,11 +53,11 @@ impl Secp256k1Felt {
         b"fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
 
     pub fn order() -> BigUint {
-        BigUint::parse_bytes(Self::SECP256K1_ORDER, 16).unwrap_or_default()
+        ORDER.clone()
     }
 
     pub fn prime() -> BigUint {
-        BigUint::parse_bytes(Self::SECP256K1_PRIME, 16).unwrap_or_default()
+        PRIME.clone()
     }
 
     pub fn new(inner: BigUint) -> Self {
@@ -40,6 +72,11 @@ impl Secp256k1Felt {
     pub fn inner(&self) -> &BigUint {
         self.0.inner()
     }
+
+    /// Returns the underlying field element without cloning it
+    pub fn into_inner(self) -> Felt {
+        self.0
+    }
 }
 
 impl From<Secp256k1Felt> for Felt {
@@ -60,17 +97,12 @@ impl Display for Secp256k1Felt {
     }
 }
 
+// Arithmetic is in the field of the prime, use `Scalar` for arithmetic modulo the order
 impl Add<Secp256k1Felt> for Secp256k1Felt {
     type Output = Secp256k1Felt;
 
     fn add(self, rhs: Secp256k1Felt) -> Self::Output {
-        let one = BigUint::from(1u32);
-        let n = Self::order();
-        
-        let sum = self.inner() + rhs.inner();
-        let sum_mod = sum.modpow(&one, &n);
-
-        Self::new(sum_mod)
+        Self(self.0 + rhs.0)
     }
 }
 
@@ -80,11 +112,7 @@ impl Mul<Secp256k1Felt> for Secp256k1Felt {
     type Output = Secp256k1Felt;
 
     fn mul(self, rhs: Secp256k1Felt) -> Self::Output {
-        let order = Self::order();
-        let one = BigUint::from(1u64);
-        let result = self.0.inner().mul(rhs.0.inner()).modpow(&one, &order);
-
-        Self::new(result)
+        Self(self.0 * rhs.0)
     }
 }
 
@@ -93,14 +121,8 @@ impl_refs!(Mul, mul, Secp256k1Felt, Secp256k1Felt);
 impl Div<Secp256k1Felt> for Secp256k1Felt {
     type Output = Secp256k1Felt;
 
-    //
     fn div(self, rhs: Secp256k1Felt) -> Self::Output {
-        let one = BigUint::from(1u32);
-        let exponent = Self::order() - BigUint::from(2u32);
-        let rhs_inner = rhs.0.inner().modpow(&exponent, &Self::order());
-        let result = (self.inner() * rhs_inner).modpow(&one, &Self::order());
-
-        Self::new(result)
+        Self(self.0 / rhs.0)
     }
 }
 
@@ -118,21 +140,62 @@ impl Secp256k1Point {
         b"483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8";
 
     pub fn order() -> BigUint {
-        BigUint::parse_bytes(Self::SECP256K1_ORDER, 16).unwrap_or_default()
+        ORDER.clone()
     }
 
     pub fn curve() -> Curve {
-        Curve::new(
-            Secp256k1Felt::new(BigUint::from(0u8)).into(),
-            Secp256k1Felt::new(BigUint::from(7u8)).into(),
-        )
+        CURVE.clone()
+    }
+
+    /// Returns the prime of the field the coordinates are in
+    pub fn field_prime() -> U256 {
+        U256::from_big_endian(&PRIME.to_bytes_be())
+    }
+
+    /// Returns the curve's `a`, which is 0
+    pub fn a() -> Felt {
+        CURVE.a.clone()
+    }
+
+    /// Returns the curve's `b`, which is 7
+    pub fn b() -> Felt {
+        CURVE.b.clone()
     }
 
     pub fn g() -> Self {
-        Self::new(
-            BigUint::parse_bytes(Self::SECP256K1_X, 16).unwrap_or_default(),
-            BigUint::parse_bytes(Self::SECP256K1_Y, 16).unwrap_or_default(),
-        )
+        GENERATOR.clone()
+    }
+
+    /// Returns a reference to the generator, which is only built once
+    pub fn generator() -> &'static Self {
+        &GENERATOR
+    }
+
+    /// Multiplies the generator by a secret scalar
+    ///
+    /// Uses a window table of the generator that is only built once, looked up with
+    /// `Point::select_ct` so that the windows of the scalar don't leak through the cache.
+    pub fn mul_generator(coefficient: &BigUint) -> Self {
+        let coefficient = coefficient.modulo(&Self::order());
+        Self(Point::windowed_mul_ct(&GENERATOR_TABLE, &coefficient))
+    }
+
+    /// Multiplies the point by a secret scalar
+    ///
+    /// Like `mul_generator`, but the window table of the point is built on every call.
+    pub fn mul_secret(&self, coefficient: &BigUint) -> Self {
+        let coefficient = coefficient.modulo(&Self::order());
+        Self(Point::windowed_mul_ct(&self.0.window_table(), &coefficient))
+    }
+
+    /// Returns the underlying point without cloning it
+    pub fn into_inner(self) -> Point {
+        self.0
+    }
+
+    /// Returns whether the point is the identity
+    pub fn is_identity(&self) -> bool {
+        self.0.is_identity()
     }
 
     pub fn x(&self) -> &PointType {
@@ -158,12 +221,190 @@ impl Secp256k1Point {
         Self(point)
     }
 
-    pub fn verify(&self, z: &Secp256k1Felt, signature: &Signature) -> bool {
-        let u = z / signature.s();
-        let v = signature.r() / signature.s();
+    /// Creates a point from its coordinates written out as strings
+    ///
+    /// Coordinates prefixed with `0x` are read as hex, anything else as decimal, which
+    /// covers how test vectors and other tools usually print them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a coordinate is not a number, is not in the field, or the
+    /// point is not on the curve
+    pub fn from_coordinates(x: &str, y: &str) -> Result<Self> {
+        let prime = Secp256k1Felt::prime();
+        let coordinate = |value: &str| -> Result<Felt> {
+            let parsed = match value.strip_prefix("0x") {
+                Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+                None => BigUint::parse_bytes(value.as_bytes(), 10),
+            };
+            let parsed = parsed.ok_or_else(|| eyre!("Invalid coordinate {value:?}"))?;
+            if parsed < prime {
+                Ok(Secp256k1Felt::new(parsed).into())
+            } else {
+                Err(eyre!("Coordinate is not in the field"))
+            }
+        };
+
+        let point = Self::curve().point(coordinate(x)?, coordinate(y)?)?;
+        Ok(Self(point))
+    }
+
+    /// Returns the point with the given x coordinate and y parity
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if x is not in the field or there is no point with that x
+    pub fn lift_x(x: &BigUint, is_odd: bool) -> Result<Self> {
+        if x >= &Secp256k1Felt::prime() {
+            return Err(eyre!("Coordinate is not in the field"));
+        }
+
+        let point = Self::curve().lift_x(Secp256k1Felt::new(x.clone()).into(), is_odd)?;
+        Ok(Self(point))
+    }
 
-        let total = Self::g() * u.inner() + self * v.inner();
-        total.x().clone().unwrap().inner() == signature.r().inner()
+    /// Serializes the point in SEC format
+    ///
+    /// Compressed form is `0x02`/`0x03` (even/odd y) followed by x, uncompressed form
+    /// is `0x04` followed by x and y, each as a 32 byte big endian integer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the point is the identity
+    pub fn to_sec(&self, compressed: bool) -> Vec<u8> {
+        let x = self.x().clone().unwrap();
+        let y = self.y().clone().unwrap();
+
+        let mut sec = Vec::with_capacity(65);
+        if compressed {
+            sec.push(if y.inner().bit(0) { 0x03 } else { 0x02 });
+            sec.extend_from_slice(&to_32_bytes_be(x.inner()));
+        } else {
+            sec.push(0x04);
+            sec.extend_from_slice(&to_32_bytes_be(x.inner()));
+            sec.extend_from_slice(&to_32_bytes_be(y.inner()));
+        }
+
+        sec
+    }
+
+    /// Serializes the point in hybrid SEC format
+    ///
+    /// Like the uncompressed form, but the prefix is `0x06`/`0x07` (even/odd y) as in the
+    /// compressed form. Hardly anything produces these, this is only for old data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the point is the identity
+    pub fn to_sec_hybrid(&self) -> Vec<u8> {
+        let mut sec = self.to_sec(false);
+        sec[0] = if self.y().clone().unwrap().inner().bit(0) { 0x07 } else { 0x06 };
+        sec
+    }
+
+    /// Parses a point from compressed, uncompressed or hybrid SEC format
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the prefix or length is invalid, the point is not on the curve,
+    /// or a hybrid prefix doesn't match the parity of y
+    pub fn from_sec(sec: &[u8]) -> Result<Self> {
+        let prime = Secp256k1Felt::prime();
+        let coordinate = |bytes: &[u8]| -> Result<Felt> {
+            let value = BigUint::from_bytes_be(bytes);
+            if value < prime {
+                Ok(Secp256k1Felt::new(value).into())
+            } else {
+                Err(eyre!("Coordinate is not in the field"))
+            }
+        };
+
+        let curve = Self::curve();
+        let mut reader = Reader::new(sec);
+        let point = match reader.read_u8()? {
+            0x04 => {
+                let x = coordinate(reader.read_bytes(32)?)?;
+                let y = coordinate(reader.read_bytes(32)?)?;
+                curve.point(x, y)?
+            }
+            prefix @ (0x06 | 0x07) => {
+                let x = coordinate(reader.read_bytes(32)?)?;
+                let y = coordinate(reader.read_bytes(32)?)?;
+                if y.inner().bit(0) != (prefix == 0x07) {
+                    return Err(eyre!("Hybrid SEC prefix doesn't match the parity of y"));
+                }
+                curve.point(x, y)?
+            }
+            prefix @ (0x02 | 0x03) => {
+                curve.lift_x(coordinate(reader.read_bytes(32)?)?, prefix == 0x03)?
+            }
+            _ => return Err(eyre!("Invalid SEC encoding")),
+        };
+
+        if !reader.is_empty() {
+            return Err(eyre!("Unexpected bytes after SEC point"));
+        }
+
+        Ok(Self(point))
+    }
+
+    /// Serializes the point in compressed SEC format as lowercase hex
+    ///
+    /// # Panics
+    ///
+    /// Panics if the point is the identity
+    pub fn to_hex_compressed(&self) -> String {
+        hex::encode(&self.to_sec(true))
+    }
+
+    /// Parses a point from hex encoded SEC, compressed or not depending on the length
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string is not hex, is neither 66 nor 130 characters long,
+    /// or `from_sec` rejects the bytes
+    pub fn from_hex(s: &str) -> Result<Self> {
+        if s.len() != 66 && s.len() != 130 {
+            return Err(eyre!("SEC hex must be 66 or 130 characters, got {}", s.len()));
+        }
+        Self::from_sec(&hex::decode(s)?)
+    }
+
+    /// Deterministically maps a message to a point on the curve
+    ///
+    /// Uses try-and-increment: `SHA256(len(domain) || domain || message || counter)` is
+    /// taken as x and lifted to the point with even y, bumping the counter until that
+    /// works. About half of the x values lift, so this takes two tries on average. This
+    /// is a simple construction, not one of the RFC 9380 hash-to-curve suites, and the
+    /// number of tries depends on the message, so it isn't constant time.
+    ///
+    /// # Panics
+    ///
+    /// Doesn't panic in practice, running out of counter values is astronomically unlikely
+    pub fn hash_to_curve(message: &[u8], domain: &[u8]) -> Self {
+        let prefix = {
+            let mut hasher = Sha256::new();
+            hasher.update((domain.len() as u64).to_le_bytes());
+            hasher.update(domain);
+            hasher.update(message);
+            hasher
+        };
+
+        for counter in 0..=u32::MAX {
+            let mut hasher = prefix.clone();
+            hasher.update(counter.to_le_bytes());
+            let x = BigUint::from_bytes_be(&hasher.finalize());
+
+            if let Ok(point) = Self::lift_x(&x, false) {
+                return point;
+            }
+        }
+
+        unreachable!("No counter value gave a point on the curve")
+    }
+
+    pub fn verify(&self, z: &Scalar, signature: &Signature) -> bool {
+        signature.verify(z, self)
     }
 }
 
@@ -181,9 +422,7 @@ impl Display for Secp256k1Point {
 
         write!(
             f,
-            "Point {{ x: 0x{x:0>64}, y: 0x{y:0>64} }}",
-            x = x_repr,
-            y = y_repr
+            "Point {{ x: 0x{x_repr:0>64}, y: 0x{y_repr:0>64} }}"
         )
     }
 }
@@ -202,7 +441,29 @@ impl Add<Secp256k1Point> for Secp256k1Point {
     }
 }
 
-impl_refs!(Add, add, Secp256k1Point, Secp256k1Point);
+impl Add<&Secp256k1Point> for &Secp256k1Point {
+    type Output = Secp256k1Point;
+
+    fn add(self, rhs: &Secp256k1Point) -> Self::Output {
+        Secp256k1Point(self.0.add_ref(&rhs.0))
+    }
+}
+
+impl Add<&Secp256k1Point> for Secp256k1Point {
+    type Output = Secp256k1Point;
+
+    fn add(self, rhs: &Secp256k1Point) -> Self::Output {
+        &self + rhs
+    }
+}
+
+impl Add<Secp256k1Point> for &Secp256k1Point {
+    type Output = Secp256k1Point;
+
+    fn add(self, rhs: Secp256k1Point) -> Self::Output {
+        self + &rhs
+    }
+}
 
 impl Mul<u32> for Secp256k1Point {
     type Output = Self;
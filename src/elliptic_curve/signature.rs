@@ -1,58 +1,296 @@
+use color_eyre::eyre::{eyre, Result};
 use num_bigint::BigUint;
 
-use crate::finite_fields::{element::Felt, pow::Pow};
+use crate::helpers::{bytes::to_32_bytes_be, reader::Reader};
 
-use super::{
-    point::PointType,
-    secp256k1::{Secp256k1Felt, Secp256k1Point},
-};
+use super::{point::PointType, scalar::Scalar, secp256k1::Secp256k1Point};
 use std::fmt::Display;
 
+/// Reasons a DER signature can fail to parse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DerError {
+    /// Shorter than 8 or longer than 72 bytes in total
+    InvalidLength(usize),
+    /// Doesn't start with the sequence tag `0x30`
+    InvalidSequenceTag,
+    /// The sequence's length byte doesn't match the bytes that follow it
+    LengthMismatch { declared: usize, actual: usize },
+    /// An integer doesn't start with the tag `0x02`
+    InvalidIntegerTag,
+    /// An integer's length runs past the end of the sequence
+    UnexpectedEnd,
+    EmptyInteger,
+    NegativeInteger,
+    /// An integer has a leading zero byte it doesn't need
+    NonMinimalInteger,
+    /// Bytes left in the sequence after s
+    TrailingBytes,
+    /// r or s is zero or not below the group order
+    InvalidScalar,
+}
+
+impl Display for DerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidLength(length) => write!(f, "Invalid DER signature length {length}"),
+            Self::InvalidSequenceTag => write!(f, "DER signature must start with 0x30"),
+            Self::LengthMismatch { declared, actual } => write!(
+                f,
+                "DER signature declares {declared} bytes of content but has {actual}"
+            ),
+            Self::InvalidIntegerTag => write!(f, "Invalid DER integer"),
+            Self::UnexpectedEnd => write!(f, "DER integer runs past the end of the signature"),
+            Self::EmptyInteger => write!(f, "DER integer can't be empty"),
+            Self::NegativeInteger => write!(f, "DER integer is negative"),
+            Self::NonMinimalInteger => write!(f, "DER integer has an unnecessary leading zero"),
+            Self::TrailingBytes => write!(f, "Unexpected bytes after DER signature"),
+            Self::InvalidScalar => write!(f, "r and s must be between 1 and n - 1"),
+        }
+    }
+}
+
+impl std::error::Error for DerError {}
+
 /// Represents a Signature on SECP256K1 curve
 #[derive(Debug, Clone, PartialEq)]
 pub struct Signature {
-    r: Secp256k1Felt,
-    s: Secp256k1Felt,
+    r: Scalar,
+    s: Scalar,
 }
 
 impl Signature {
     /// Creates a new Signature
-    pub fn new(r: Secp256k1Felt, s: Secp256k1Felt) -> Self {
+    pub fn new(r: Scalar, s: Scalar) -> Self {
         Self { r, s }
     }
 
     /// Returns r of the signature
-    pub fn r(&self) -> &Secp256k1Felt {
+    pub fn r(&self) -> &Scalar {
         &self.r
     }
 
     /// Returns s of the signature
-    pub fn s(&self) -> &Secp256k1Felt {
+    pub fn s(&self) -> &Scalar {
         &self.s
     }
 
+    /// Serializes the signature in compact form, `r || s` as 32 byte big endian integers
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&to_32_bytes_be(self.r.inner()));
+        bytes[32..].copy_from_slice(&to_32_bytes_be(self.s.inner()));
+        bytes
+    }
+
+    /// Parses a signature from its compact form
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if r or s is zero or not below the group order
+    pub fn from_bytes(bytes: &[u8; 64]) -> Result<Self> {
+        let r = BigUint::from_bytes_be(&bytes[..32]);
+        let s = BigUint::from_bytes_be(&bytes[32..]);
+        Self::from_integers(r, s)
+    }
+
+    /// Serializes the signature in DER format
+    ///
+    /// `0x30 <length> 0x02 <r length> <r> 0x02 <s length> <s>`, where r and s are
+    /// minimal big endian integers. DER integers are signed, so a `0x00` is prepended
+    /// when the most significant bit is set, and only then.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn to_der(&self) -> Vec<u8> {
+        let encode_integer = |value: &BigUint| {
+            let mut bytes = value.to_bytes_be();
+            if bytes[0] & 0x80 != 0 {
+                bytes.insert(0, 0x00);
+            }
+
+            // At most 33 bytes since r and s are below the group order
+            let mut encoded = vec![0x02, bytes.len() as u8];
+            encoded.extend_from_slice(&bytes);
+            encoded
+        };
+
+        let mut content = encode_integer(self.r.inner());
+        content.extend(encode_integer(self.s.inner()));
+
+        let mut der = vec![0x30, content.len() as u8];
+        der.extend(content);
+        der
+    }
+
+    /// Parses a signature in strict DER format (BIP66)
+    ///
+    /// Like consensus, the sequence length byte has to match the content exactly, so
+    /// a signature with a wrong length byte or garbage appended is rejected rather
+    /// than read up to where it seems to end.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the encoding is malformed, if the declared length doesn't
+    /// match the content, if an integer is negative or has an unnecessary leading zero
+    /// byte, or if r or s is not a valid scalar
+    pub fn from_der(der: &[u8]) -> Result<Self, DerError> {
+        if der.len() < 8 || der.len() > 72 {
+            return Err(DerError::InvalidLength(der.len()));
+        }
+        if der[0] != 0x30 {
+            return Err(DerError::InvalidSequenceTag);
+        }
+        let declared = usize::from(der[1]);
+        let actual = der.len() - 2;
+        if declared != actual {
+            return Err(DerError::LengthMismatch { declared, actual });
+        }
+
+        let mut reader = Reader::new(&der[2..]);
+        let r = Self::read_der_integer(&mut reader)?;
+        let s = Self::read_der_integer(&mut reader)?;
+        if !reader.is_empty() {
+            return Err(DerError::TrailingBytes);
+        }
+
+        Self::from_integers(r, s).map_err(|_| DerError::InvalidScalar)
+    }
+
+    /// Reads a DER integer
+    fn read_der_integer(reader: &mut Reader<'_>) -> Result<BigUint, DerError> {
+        if reader.read_u8().map_err(|_| DerError::UnexpectedEnd)? != 0x02 {
+            return Err(DerError::InvalidIntegerTag);
+        }
+
+        let length = usize::from(reader.read_u8().map_err(|_| DerError::UnexpectedEnd)?);
+        if length == 0 {
+            return Err(DerError::EmptyInteger);
+        }
+
+        let value = reader.read_bytes(length).map_err(|_| DerError::UnexpectedEnd)?;
+        if value[0] & 0x80 != 0 {
+            return Err(DerError::NegativeInteger);
+        }
+        if value.len() > 1 && value[0] == 0x00 && value[1] & 0x80 == 0 {
+            return Err(DerError::NonMinimalInteger);
+        }
+
+        Ok(BigUint::from_bytes_be(value))
+    }
+
+    /// Creates a signature from r and s, checking they are valid scalars
+    fn from_integers(r: BigUint, s: BigUint) -> Result<Self> {
+        let n = Secp256k1Point::order();
+        let zero = BigUint::from(0u32);
+
+        if r == zero || r >= n {
+            return Err(eyre!("r must be between 1 and n - 1"));
+        }
+        if s == zero || s >= n {
+            return Err(eyre!("s must be between 1 and n - 1"));
+        }
+
+        Ok(Self::new(Scalar::new(r), Scalar::new(s)))
+    }
+
+    /// Returns whether s is in the lower half of the group order
+    ///
+    /// BIP62 only allows these, since `(r, n - s)` is also a valid signature for the
+    /// same message and key.
+    pub fn is_low_s(&self) -> bool {
+        self.s.inner() <= &(Secp256k1Point::order() / BigUint::from(2u32))
+    }
+
+    /// Returns the sum of the s values of the signatures modulo the group order
+    ///
+    /// Experimental: this is one building block for trying out half-aggregation, not an
+    /// aggregation scheme. The sum on its own doesn't verify against anything.
+    pub fn aggregate_s(signatures: &[Signature]) -> Scalar {
+        signatures
+            .iter()
+            .fold(Scalar::default(), |sum, signature| sum + signature.s())
+    }
+
     /// Verifies the signature, given the message, signature and the public key
-    #[allow(clippy::many_single_char_names)]
+    ///
+    /// Signatures with a high s are rejected, see `is_low_s`
     pub fn verify(
         &self,
-        z: &Secp256k1Felt,
+        z: &Scalar,
+        public_key: &Secp256k1Point,
+    ) -> bool {
+        self.is_low_s() && self.verify_malleable(z, public_key)
+    }
+
+    /// Verifies the signature like `verify`, but also accepts a high s
+    ///
+    /// Anyone can flip s to `n - s` without knowing the private key, so a signature
+    /// that passes here can have a second encoding. Only use this to check the math
+    /// on signatures from elsewhere, never to decide whether something is unique.
+    #[allow(clippy::many_single_char_names)]
+    pub fn verify_malleable(
+        &self,
+        z: &Scalar,
         public_key: &Secp256k1Point,
     ) -> bool {
         let g = Secp256k1Point::g();
+        let Some(s_inverse) = self.s().inverse() else {
+            return false;
+        };
 
-        let u = z / self.s();
+        let u = z * &s_inverse;
         let u = g * u.inner();
 
-        let v = self.r() / self.s();
+        let v = self.r() * &s_inverse;
         let v = public_key * v.inner();
 
-        let p = u + v;
+        // r is the x coordinate reduced modulo the group order
+        match (u + v).x() {
+            PointType::Infinity => false,
+            PointType::Normal(x) => &Scalar::new(x.inner().clone()) == self.r(),
+        }
+    }
+
+    /// Recovers the public key that produced the signature, given the recovery id
+    ///
+    /// The lowest bit of the recovery id is the parity of `R.y` and the next bit tells
+    /// whether `R.x` was larger than the group order, so that `r = R.x - n`.
+    /// The public key is then `r^-1 * (s * R - z * G)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the recovery id is larger than 3 or there is no valid key for it
+    pub fn recover(&self, z: &Scalar, recovery_id: u8) -> Result<Secp256k1Point> {
+        if recovery_id > 3 {
+            return Err(eyre!("Recovery id must be between 0 and 3"));
+        }
+
+        let n = Secp256k1Point::order();
         let r = self.r().inner();
 
-        match p.x() {
-            PointType::Infinity => false,
-            PointType::Normal(x) => x.inner() == r,
+        let mut x = r.clone();
+        if recovery_id & 2 == 2 {
+            x += &n;
         }
+        let nonce_point = Secp256k1Point::lift_x(&x, recovery_id & 1 == 1)?;
+
+        // u1 = -z / r, u2 = s / r
+        let r_inverse = self.r().inverse().ok_or_else(|| eyre!("r must not be zero"))?;
+        let u1 = -z * &r_inverse;
+        let u2 = self.s() * &r_inverse;
+
+        let public_key = Secp256k1Point::g() * u1.inner() + nonce_point * u2.inner();
+        if public_key.is_identity() {
+            return Err(eyre!("Recovered key is the point at infinity"));
+        }
+        Ok(public_key)
+    }
+
+    /// Recovers every public key the signature is valid for
+    ///
+    /// Without the recovery id there are up to four candidates, one for each id.
+    pub fn recover_all(&self, z: &Scalar) -> Vec<Secp256k1Point> {
+        (0..4)
+            .filter_map(|recovery_id| self.recover(z, recovery_id).ok())
+            .collect()
     }
 
     /// Convenience method to verify a signature given a message as a slice
@@ -61,7 +299,7 @@ impl Signature {
         z: &[u8],
         public_key: &Secp256k1Point,
     ) -> bool {
-        let z = Secp256k1Felt::from_bytes(z);
+        let z = Scalar::from_bytes(z);
         self.verify(&z, public_key)
     }
 }
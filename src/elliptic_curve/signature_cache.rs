@@ -0,0 +1,98 @@
+use super::{scalar::Scalar, secp256k1::Secp256k1Point, signature::Signature};
+use std::collections::{HashMap, VecDeque};
+
+/// `(z, r || s, compressed SEC)`, everything a verification result depends on
+type CacheKey = ([u8; 32], [u8; 64], Vec<u8>);
+
+/// Cache of signature verification results
+///
+/// The same signature is often checked more than once, e.g. when a transaction is
+/// accepted and then again when it shows up in a block. Holds up to `capacity` results
+/// and evicts the oldest one when full.
+#[derive(Debug, Clone)]
+pub struct SignatureCache {
+    capacity: usize,
+    results: HashMap<CacheKey, bool>,
+    order: VecDeque<CacheKey>,
+}
+
+impl SignatureCache {
+    /// Creates an empty cache that keeps at most `capacity` results
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            results: HashMap::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of cached results
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Returns whether the cache has no results
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Returns the cached result for the signature, if there is one
+    pub fn get(
+        &self,
+        z: &Scalar,
+        signature: &Signature,
+        public_key: &Secp256k1Point,
+    ) -> Option<bool> {
+        Self::key(z, signature, public_key).and_then(|key| self.results.get(&key).copied())
+    }
+
+    /// Caches a verification result, e.g. one that was checked in a batch
+    pub fn insert(
+        &mut self,
+        z: &Scalar,
+        signature: &Signature,
+        public_key: &Secp256k1Point,
+        valid: bool,
+    ) {
+        let Some(key) = Self::key(z, signature, public_key) else {
+            return;
+        };
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.results.insert(key.clone(), valid).is_none() {
+            if self.order.len() == self.capacity {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.results.remove(&oldest);
+                }
+            }
+            self.order.push_back(key);
+        }
+    }
+
+    /// Verifies the signature like `Signature::verify`, returning the cached result if
+    /// there is one and caching the result otherwise
+    pub fn verify(
+        &mut self,
+        z: &Scalar,
+        signature: &Signature,
+        public_key: &Secp256k1Point,
+    ) -> bool {
+        if let Some(valid) = self.get(z, signature, public_key) {
+            return valid;
+        }
+
+        let valid = signature.verify(z, public_key);
+        self.insert(z, signature, public_key, valid);
+        valid
+    }
+
+    /// The identity has no SEC encoding, so its results are never cached
+    fn key(z: &Scalar, signature: &Signature, public_key: &Secp256k1Point) -> Option<CacheKey> {
+        if public_key.is_identity() {
+            return None;
+        }
+        Some((z.to_bytes(), signature.to_bytes(), public_key.to_sec(true)))
+    }
+}
@@ -1,10 +1,44 @@
 use super::{macros::impl_refs, modulo::Modulo, pow::Pow};
+use color_eyre::eyre::{eyre, Result};
 use num_bigint::BigUint;
+use primitive_types::U256;
+use subtle::{Choice, ConditionallySelectable};
 use std::{
     fmt::Display,
     ops::{Add, Div, Mul, Sub},
 };
 
+/// Reasons a field operation can fail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldError {
+    /// No points were given where at least one is needed
+    NoPoints,
+    /// Two points share an x coordinate
+    DuplicateX,
+    /// A value isn't less than the prime
+    OutOfRange,
+}
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoPoints => write!(f, "At least one point is needed"),
+            Self::DuplicateX => write!(f, "Points must have distinct x coordinates"),
+            Self::OutOfRange => write!(f, "Value must be less than prime"),
+        }
+    }
+}
+
+impl std::error::Error for FieldError {}
+
+/// Returns whether a value is an element of the field of the prime, `inner < prime`
+///
+/// `Felt::new` panics on anything else, so parsers can check with this first and
+/// surface their own error instead, or call `Felt::try_new`.
+pub fn in_range(inner: &BigUint, prime: &BigUint) -> bool {
+    inner < prime
+}
+
 /// Represents a field element
 ///
 /// Prime used in secp256k1 is pretty large and values used in the field can be
@@ -36,7 +70,33 @@ impl Felt {
     /// assert_eq!(felt.prime(), &prime);
     /// ```
     pub fn new(inner: BigUint, prime: BigUint) -> Self {
-        assert!(inner < prime, "Inner value must be less than prime");
+        assert!(in_range(&inner, &prime), "Inner value must be less than prime");
+        Self { inner, prime }
+    }
+
+    /// Creates a new field element, or an error where `new` would panic
+    ///
+    /// # Errors
+    ///
+    /// Returns `FieldError::OutOfRange` if the inner value is greater than or equal to
+    /// the prime
+    pub fn try_new(inner: BigUint, prime: BigUint) -> Result<Self, FieldError> {
+        if !in_range(&inner, &prime) {
+            return Err(FieldError::OutOfRange);
+        }
+        Ok(Self { inner, prime })
+    }
+
+    /// Creates a new field element without checking the range of the inner value
+    ///
+    /// Arithmetic operators use this after reducing their result modulo the prime,
+    /// which keeps the range check out of the scalar multiplication loop.
+    ///
+    /// # Safety
+    ///
+    /// `inner` must be less than `prime`. This is only checked in debug builds.
+    pub unsafe fn new_unchecked(inner: BigUint, prime: BigUint) -> Self {
+        debug_assert!(inner < prime, "Inner value must be less than prime");
         Self { inner, prime }
     }
 
@@ -47,6 +107,320 @@ impl Felt {
     pub fn prime(&self) -> &BigUint {
         &self.prime
     }
+
+    /// Checks that the inner value is in range, only in debug builds
+    fn debug_invariant(&self) {
+        debug_assert!(
+            self.inner < self.prime,
+            "Inner value must be less than prime"
+        );
+    }
+
+    /// Checks that both operands are valid elements of the same field, only in debug builds
+    fn debug_same_field(&self, other: &Self) {
+        self.debug_invariant();
+        other.debug_invariant();
+        debug_assert!(
+            self.prime == other.prime,
+            "Operands must be in the same field"
+        );
+    }
+
+    /// Returns the value of the element as a `BigUint`
+    pub fn to_biguint(&self) -> BigUint {
+        self.inner.clone()
+    }
+
+    /// Creates a field element from a `BigUint`
+    ///
+    /// # Errors
+    ///
+    /// Returns `FieldError::OutOfRange` if the value is not less than the prime
+    pub fn from_biguint(value: &BigUint, prime: &BigUint) -> Result<Self, FieldError> {
+        Self::try_new(value.clone(), prime.clone())
+    }
+
+    /// Returns the value of the element as a `U256`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value doesn't fit in 256 bits
+    pub fn to_u256(&self) -> Result<U256> {
+        let bytes = self.inner.to_bytes_be();
+        if bytes.len() > 32 {
+            return Err(eyre!("Value doesn't fit in 256 bits"));
+        }
+        Ok(U256::from_big_endian(&bytes))
+    }
+
+    /// Creates a field element from a `U256` value and prime
+    ///
+    /// # Errors
+    ///
+    /// Returns `FieldError::OutOfRange` if the value is not less than the prime
+    pub fn from_u256(value: U256, prime: U256) -> Result<Self, FieldError> {
+        let mut value_bytes = [0u8; 32];
+        let mut prime_bytes = [0u8; 32];
+        value.to_big_endian(&mut value_bytes);
+        prime.to_big_endian(&mut prime_bytes);
+
+        Self::from_biguint(
+            &BigUint::from_bytes_be(&value_bytes),
+            &BigUint::from_bytes_be(&prime_bytes),
+        )
+    }
+
+    /// Returns `self * 2^k`
+    ///
+    /// Shifts at most 64 bits at a time and reduces in between, so a large `k` doesn't
+    /// build a huge intermediate value.
+    pub fn shl_mod(&self, k: u32) -> Self {
+        self.debug_invariant();
+
+        let mut result = self.inner.clone();
+        let mut remaining = k;
+        while remaining > 0 {
+            let shift = remaining.min(64);
+            result = (result << shift).modulo(&self.prime);
+            remaining -= shift;
+        }
+
+        // SAFETY: result is reduced modulo prime, or is the already reduced inner value
+        unsafe { Self::new_unchecked(result, self.prime.clone()) }
+    }
+
+    /// Returns the multiplicative inverse of the element, or zero for zero
+    ///
+    /// Computed as `a^(p-2)`, which is the inverse by Fermat's little theorem. Zero is
+    /// returned early: `0^(p-2)` is zero for odd primes, but for `p = 2` the exponent is
+    /// zero and `modpow` would give 1.
+    pub fn invert_or_zero(&self) -> Self {
+        self.debug_invariant();
+
+        if self.inner == BigUint::from(0u32) {
+            return self.clone();
+        }
+
+        let exponent = &self.prime - BigUint::from(2u32);
+        let result = self.inner.modpow(&exponent, &self.prime);
+        // SAFETY: modpow reduces modulo prime
+        unsafe { Self::new_unchecked(result, self.prime.clone()) }
+    }
+
+    /// Returns the smallest `k > 0` with `self^k == 1`, or `None` for zero
+    ///
+    /// Tries every k up to `p - 1`, so this is only meant for small teaching fields.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the prime doesn't fit in a `u64`
+    pub fn multiplicative_order(&self) -> Option<u64> {
+        let limit = u64::try_from(&self.prime).expect("Field is too large to find orders in");
+        let one = BigUint::from(1u32);
+
+        let mut power = self.inner.clone();
+        for k in 1..limit {
+            if power == one {
+                return Some(k);
+            }
+            power = (power * &self.inner).modulo(&self.prime);
+        }
+
+        None
+    }
+
+    /// Returns whether the element generates the multiplicative group of the field
+    ///
+    /// That is when its order is `p - 1`, which holds exactly when `self^((p - 1) / q)`
+    /// isn't 1 for any prime factor `q` of `p - 1`. The factors are found by trial
+    /// division, so this is only meant for small teaching fields.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the prime doesn't fit in a `u64`
+    pub fn is_primitive_root(&self) -> bool {
+        let group_order =
+            u64::try_from(&self.prime).expect("Field is too large to find primitive roots in") - 1;
+        if self.inner == BigUint::from(0u32) {
+            return false;
+        }
+
+        let one = BigUint::from(1u32);
+        let mut remaining = group_order;
+        let mut factor = 2;
+        while remaining > 1 {
+            if factor * factor > remaining {
+                // What is left has no smaller factors, so it is prime
+                factor = remaining;
+            }
+            if remaining % factor == 0 {
+                let exponent = BigUint::from(group_order / factor);
+                if self.inner.modpow(&exponent, &self.prime) == one {
+                    return false;
+                }
+                while remaining % factor == 0 {
+                    remaining /= factor;
+                }
+            }
+            factor += 1;
+        }
+
+        true
+    }
+
+    /// Sets `self` to `src` when `choice` is set, without branching on it
+    ///
+    /// Both values are written out as big endian bytes as wide as the prime and every
+    /// byte goes through `subtle`, so which one is kept doesn't show in the branches or
+    /// memory accesses. Building the result as a `BigUint` still isn't constant time.
+    /// The prime stays the same.
+    pub fn conditional_assign(&mut self, src: &Felt, choice: Choice) {
+        self.debug_invariant();
+        self.debug_same_field(src);
+
+        let width = self.prime.to_bytes_be().len();
+        let padded = |value: &BigUint| {
+            let bytes = value.to_bytes_be();
+            let mut padded = vec![0u8; width];
+            padded[width - bytes.len()..].copy_from_slice(&bytes);
+            padded
+        };
+
+        let mut bytes = padded(&self.inner);
+        for (byte, src_byte) in bytes.iter_mut().zip(padded(&src.inner)) {
+            byte.conditional_assign(&src_byte, choice);
+        }
+        self.inner = BigUint::from_bytes_be(&bytes);
+    }
+
+    /// Returns whichever of `self` and `-self` has the requested parity
+    ///
+    /// For an odd prime exactly one of them is odd, unless the element is zero, which is
+    /// returned as is. Picks the y coordinate out of the two square roots when lifting x.
+    pub fn with_parity(&self, want_odd: bool) -> Self {
+        self.debug_invariant();
+
+        if self.inner.bit(0) == want_odd || self.inner == BigUint::from(0u32) {
+            return self.clone();
+        }
+        // SAFETY: inner is nonzero and below prime, so prime - inner is too
+        unsafe { Self::new_unchecked(&self.prime - &self.inner, self.prime.clone()) }
+    }
+
+    /// Returns whether the prime is 3 modulo 4, where square roots are a single power
+    pub fn is_prime_3_mod_4(&self) -> bool {
+        &self.prime % 4u32 == BigUint::from(3u32)
+    }
+
+    /// Returns whether the prime is 1 modulo 4, where square roots need Tonelli–Shanks
+    pub fn is_prime_1_mod_4(&self) -> bool {
+        &self.prime % 4u32 == BigUint::from(1u32)
+    }
+
+    /// Returns whether the element is a nonzero square in the field
+    ///
+    /// Uses Euler's criterion, `a^((p - 1) / 2)` is 1 for squares and `p - 1` otherwise
+    pub fn is_quadratic_residue(&self) -> bool {
+        let one = BigUint::from(1u32);
+        let exponent = (&self.prime - &one) >> 1;
+        self.inner.modpow(&exponent, &self.prime) == one
+    }
+
+    /// Creates a field element from a big endian integer of any width, reducing it
+    ///
+    /// Hash-to-field and similar code start from more bytes than the prime has, e.g. 64
+    /// bytes to keep the bias of the reduction negligible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the prime is zero
+    pub fn from_wide_bytes(bytes: &[u8], prime: U256) -> Self {
+        assert!(!prime.is_zero(), "Prime must not be zero");
+
+        let mut prime_bytes = [0u8; 32];
+        prime.to_big_endian(&mut prime_bytes);
+        let prime = BigUint::from_bytes_be(&prime_bytes);
+
+        let inner = BigUint::from_bytes_be(bytes).modulo(&prime);
+        // SAFETY: inner is reduced modulo prime
+        unsafe { Self::new_unchecked(inner, prime) }
+    }
+
+    /// Returns a square root of the element, if there is one
+    ///
+    /// Uses the `p ≡ 3 (mod 4)` shortcut when possible and falls back to
+    /// Tonelli–Shanks for any other odd prime. The other root is `prime - root`.
+    #[allow(clippy::many_single_char_names)]
+    pub fn sqrt(&self) -> Option<Self> {
+        let zero = BigUint::from(0u32);
+        let one = BigUint::from(1u32);
+        let two = BigUint::from(2u32);
+        let p = &self.prime;
+
+        if self.inner == zero || p == &two {
+            return Some(self.clone());
+        }
+
+        if !self.is_quadratic_residue() {
+            return None;
+        }
+
+        let p_minus_one = p - &one;
+        if self.is_prime_3_mod_4() {
+            let root = self.inner.modpow(&((p + &one) / 4u32), p);
+            return Some(Self::new(root, p.clone()));
+        }
+
+        // Write p - 1 as q * 2^s with q odd
+        let mut q = p_minus_one.clone();
+        let mut s = 0u32;
+        while &q % &two == zero {
+            q /= &two;
+            s += 1;
+        }
+
+        // Any quadratic non-residue works as z
+        let mut z = two.clone();
+        while z.modpow(&(&p_minus_one / &two), p) == one {
+            z += &one;
+        }
+
+        let mut m = s;
+        let mut c = z.modpow(&q, p);
+        let mut t = self.inner.modpow(&q, p);
+        let mut root = self.inner.modpow(&((&q + &one) / &two), p);
+
+        while t != one {
+            // Find the least i such that t^(2^i) == 1
+            let mut i = 0u32;
+            let mut t_pow = t.clone();
+            while t_pow != one {
+                t_pow = (&t_pow * &t_pow) % p;
+                i += 1;
+            }
+
+            let b = c.modpow(&(BigUint::from(1u32) << (m - i - 1)), p);
+            m = i;
+            c = (&b * &b) % p;
+            t = (t * &c) % p;
+            root = (root * b) % p;
+        }
+
+        Some(Self::new(root, p.clone()))
+    }
+
+    /// Evaluates the polynomial `coeffs[0] + coeffs[1] * x + coeffs[2] * x^2 + ...` at `x`
+    ///
+    /// Uses Horner's rule, folding from the highest coefficient down, so it takes one
+    /// multiplication and one addition per coefficient. An empty polynomial is zero.
+    pub fn eval_poly(coeffs: &[Felt], x: &Felt) -> Felt {
+        // SAFETY: zero is below any prime
+        let zero = unsafe { Self::new_unchecked(BigUint::from(0u32), x.prime.clone()) };
+        coeffs
+            .iter()
+            .rev()
+            .fold(zero, |acc, coefficient| acc * x + coefficient)
+    }
 }
 
 impl PartialEq for Felt {
@@ -71,8 +445,10 @@ impl Add for Felt {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
+        self.debug_same_field(&rhs);
         let result = (self.inner + rhs.inner).modulo(&self.prime);
-        Self::new(result, self.prime)
+        // SAFETY: result is reduced modulo prime
+        unsafe { Self::new_unchecked(result, self.prime) }
     }
 }
 
@@ -82,13 +458,15 @@ impl Sub for Felt {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
+        self.debug_same_field(&rhs);
         let result = if self.inner > rhs.inner {
             &self.inner - rhs.inner
         } else {
             &self.prime - (rhs.inner - self.inner)
         };
 
-        Self::new(result.modulo(&self.prime), self.prime.clone())
+        // SAFETY: result is reduced modulo prime
+        unsafe { Self::new_unchecked(result.modulo(&self.prime), self.prime.clone()) }
     }
 }
 
@@ -98,8 +476,10 @@ impl Mul for Felt {
     type Output = Self;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        let result =  (self.inner * rhs.inner).modulo(&self.prime);
-        Self::new(result, self.prime)
+        self.debug_same_field(&rhs);
+        let result = (self.inner * rhs.inner).modulo(&self.prime);
+        // SAFETY: result is reduced modulo prime
+        unsafe { Self::new_unchecked(result, self.prime) }
     }
 }
 
@@ -108,8 +488,10 @@ impl Mul<u32> for Felt {
 
     /// Scalar multiplication for Felt
     fn mul(self, rhs: u32) -> Self::Output {
+        self.debug_invariant();
         let result: BigUint = self.inner.mul(BigUint::from(rhs)).modulo(&self.prime);
-        Self::new(result, self.prime)
+        // SAFETY: result is reduced modulo prime
+        unsafe { Self::new_unchecked(result, self.prime) }
     }
 }
 
@@ -120,9 +502,11 @@ impl Div for Felt {
     type Output = Self;
 
     fn div(self, rhs: Self) -> Self::Output {
+        self.debug_same_field(&rhs);
         let exponent = &self.prime - BigUint::from(2u32);
         let result = (self.inner * rhs.inner.modpow(&exponent, &self.prime)).modulo(&self.prime);
-        Self::new(result, self.prime)
+        // SAFETY: result is reduced modulo prime
+        unsafe { Self::new_unchecked(result, self.prime) }
     }
 }
 
@@ -132,17 +516,38 @@ impl Pow<u32> for Felt {
     type Output = Felt;
 
     fn pow(&self, exponent: u32) -> Self::Output {
-        let exponent = BigUint::from(exponent).modulo(&self.prime);
-        let result = self
-            .inner
-            .pow(exponent.try_into().unwrap())
-            .modulo(&self.prime);
-
-        Self::new(result, self.prime.clone())
+        self.debug_invariant();
+
+        // By Fermat's little theorem a^(p-1) = 1 for a nonzero a, so the exponent can be
+        // reduced modulo p-1. Multiples of p-1 then become 0 and give 1 as they should,
+        // but zero to any positive power is still zero so it can't take that path.
+        if self.inner == BigUint::from(0u32) && exponent > 0 {
+            return self.clone();
+        }
+
+        let order = &self.prime - BigUint::from(1u32);
+        let mut exponent = u32::try_from(BigUint::from(exponent).modulo(&order)).unwrap();
+
+        // Square and multiply, reducing every step. Curve arithmetic mostly raises to
+        // 2 or 3, where this is cheaper than modpow's setup.
+        let mut result = BigUint::from(1u32).modulo(&self.prime);
+        let mut base = self.inner.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = (result * &base).modulo(&self.prime);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = (&base * &base).modulo(&self.prime);
+            }
+        }
+
+        // SAFETY: result is reduced modulo prime
+        unsafe { Self::new_unchecked(result, self.prime.clone()) }
     }
 }
 
-impl<'b> Pow<u32> for &'b Felt {
+impl Pow<u32> for &Felt {
     type Output = Felt;
 
     fn pow(&self, exponent: u32) -> Self::Output {
@@ -155,6 +560,8 @@ impl Pow<i64> for Felt {
     type Output = Felt;
 
     fn pow(&self, exponent: i64) -> Self::Output {
+        self.debug_invariant();
+
         let inner = if exponent > 0 {
             let exponent = BigUint::from(u32::try_from(exponent).unwrap());
             self.inner.modpow(&exponent, &self.prime)
@@ -166,11 +573,12 @@ impl Pow<i64> for Felt {
             self.inner.modpow(&exponent, &self.prime)
         };
 
-        Felt::new(inner, self.prime.clone())
+        // SAFETY: modpow reduces modulo prime
+        unsafe { Felt::new_unchecked(inner, self.prime.clone()) }
     }
 }
 
-impl<'b> Pow<i64> for &'b Felt {
+impl Pow<i64> for &Felt {
     type Output = Felt;
 
     fn pow(&self, exponent: i64) -> Self::Output {
@@ -0,0 +1,33 @@
+use super::element::{FieldError, Felt};
+use num_bigint::BigUint;
+
+/// Evaluates the polynomial through the given points at `x` by Lagrange interpolation
+///
+/// `n` points with distinct x coordinates determine a single polynomial of degree below
+/// `n`, whose value at `x` is `sum y_i * prod (x - x_j) / (x_i - x_j)` over `j != i`.
+/// Shamir's secret sharing recovers its secret by interpolating at zero. With no points
+/// the result is zero.
+///
+/// # Errors
+///
+/// Returns `FieldError::DuplicateX` if two points share an x coordinate, since the basis
+/// polynomials would divide by zero
+pub fn lagrange_interpolate(points: &[(Felt, Felt)], x: &Felt) -> Result<Felt, FieldError> {
+    for (i, (x_i, _)) in points.iter().enumerate() {
+        if points[i + 1..].iter().any(|(x_j, _)| x_j == x_i) {
+            return Err(FieldError::DuplicateX);
+        }
+    }
+
+    let felt = |value: u32| Felt::new(BigUint::from(value), x.prime().clone());
+    let value = points.iter().fold(felt(0), |value, (x_i, y_i)| {
+        let basis = points
+            .iter()
+            .filter(|(x_j, _)| x_j != x_i)
+            .fold(felt(1), |basis, (x_j, _)| basis * (x - x_j) / (x_i - x_j));
+
+        value + basis * y_i
+    });
+
+    Ok(value)
+}
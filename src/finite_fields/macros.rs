@@ -1,11 +1,13 @@
 /// Convenience macro for creating a new Felt
 macro_rules! felt {
-    ($num:expr, $prime: expr) => {
-        crate::finite_fields::element::Felt::new(
+    ($num:expr, $prime: expr) => {{
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let felt = crate::finite_fields::element::Felt::new(
             ::num_bigint::BigUint::from($num as u32), 
             ::num_bigint::BigUint::from($prime as u32)
-        )
-    };
+        );
+        felt
+    }};
 }
 
 /// Implements a trait for all reference combinations (&T-U, T-&U, &T-&U) 
@@ -1,5 +1,6 @@
 #![allow(unused)]
 pub mod element;
+pub mod interpolation;
 pub mod macros;
 pub mod modulo;
 pub mod pow;
@@ -8,11 +9,14 @@ pub mod pow;
 
 #[cfg(test)]
 mod tests {
-    use crate::finite_fields::pow::Pow;
+    use crate::finite_fields::pow::{self, Pow};
+    use num_bigint::BigUint;
+    use primitive_types::U256;
 
-    use super::macros::felt;
+    use super::{element::{self, Felt}, interpolation::lagrange_interpolate, macros::felt};
 
     #[test]
+    #[allow(clippy::semicolon_if_nothing_returned, clippy::uninlined_format_args)]
     fn test_display() {
         let felt_a = felt!(1, 11);
         assert_eq!(format!("{}", felt_a), "Field Element: 1 (11)")
@@ -47,4 +51,477 @@ mod tests {
         let felt_b = felt!(1, 19) / felt!(3, 19);
         assert_eq!(felt_a, felt_b);
     }
+
+    #[test]
+    fn test_new_unchecked() {
+        let felt_a = unsafe { Felt::new_unchecked(BigUint::from(5u32), BigUint::from(19u32)) };
+        assert_eq!(felt_a, felt!(5, 19));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Inner value must be less than prime")]
+    fn test_new_unchecked_out_of_range() {
+        let _ = unsafe { Felt::new_unchecked(BigUint::from(19u32), BigUint::from(19u32)) };
+    }
+
+    #[test]
+    fn test_sqrt() {
+        // 17 ≡ 1 (mod 4) goes through Tonelli–Shanks, 19 ≡ 3 (mod 4) takes the shortcut
+        for prime in [17u32, 19] {
+            for value in 0..prime {
+                let felt = felt!(value, prime);
+                let is_residue = (0..prime).any(|y| y * y % prime == value);
+
+                match felt.sqrt() {
+                    Some(root) => assert_eq!(root.pow(2u32), felt),
+                    None => assert!(!is_residue),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_pow_fermat_boundary() {
+        let prime = 13u32;
+        for a in 1..prime {
+            assert_eq!(felt!(a, prime).pow(prime - 1), felt!(1, prime));
+            assert_eq!(felt!(a, prime).pow(2 * (prime - 1)), felt!(1, prime));
+            assert_eq!(felt!(a, prime).pow(prime), felt!(a, prime));
+            assert_eq!(felt!(a, prime).pow(0u32), felt!(1, prime));
+        }
+
+        assert_eq!(felt!(0, prime).pow(prime - 1), felt!(0, prime));
+        assert_eq!(felt!(0, prime).pow(0u32), felt!(1, prime));
+    }
+
+    #[test]
+    fn test_biguint_u256_bridge() {
+        let prime = BigUint::parse_bytes(
+            b"fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+            16,
+        )
+        .unwrap();
+        let value = BigUint::parse_bytes(b"deadbeef0123456789abcdef", 16).unwrap();
+
+        let felt = Felt::from_biguint(&value, &prime).unwrap();
+        assert_eq!(felt.to_biguint(), value);
+        assert_eq!(Felt::from_biguint(&prime, &prime), Err(element::FieldError::OutOfRange));
+
+        let as_u256 = felt.to_u256().unwrap();
+        assert_eq!(
+            as_u256,
+            U256::from_str_radix("deadbeef0123456789abcdef", 16).unwrap()
+        );
+
+        let prime_u256 = U256::from_big_endian(&prime.to_bytes_be());
+        assert_eq!(Felt::from_u256(as_u256, prime_u256).unwrap(), felt);
+        assert_eq!(
+            Felt::from_u256(prime_u256, prime_u256),
+            Err(element::FieldError::OutOfRange)
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Inner value must be less than prime")]
+    fn test_corrupted_felt_trips_invariant() {
+        // Default gives 0 with a prime of 0, which is not a valid element
+        let _ = Felt::default() + felt!(1, 7);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Operands must be in the same field")]
+    fn test_mismatched_primes_trip_invariant() {
+        let _ = felt!(3, 7) * felt!(3, 11);
+    }
+
+    #[test]
+    fn test_is_quadratic_residue() {
+        let squares: Vec<u32> = (1..19u32).map(|x| x * x % 19).collect();
+        for value in 1..19u32 {
+            assert_eq!(felt!(value, 19).is_quadratic_residue(), squares.contains(&value));
+        }
+        assert!(!felt!(0, 19).is_quadratic_residue());
+    }
+
+    #[test]
+    fn test_shl_mod() {
+        let prime = 223u32;
+        for value in [0u32, 1, 47, 222] {
+            for k in 0..20 {
+                let power = (1u64 << k) % u64::from(prime);
+                let expected = felt!(value, prime) * felt!(power, prime);
+                assert_eq!(felt!(value, prime).shl_mod(k), expected);
+            }
+        }
+
+        // Large shifts go through several reductions
+        let p = BigUint::from(prime);
+        let power = BigUint::from(2u32).modpow(&BigUint::from(200u32), &p);
+        let x = felt!(47, prime);
+        assert_eq!(x.shl_mod(200), &x * Felt::new(power, p));
+    }
+
+    #[test]
+    fn test_invert_or_zero() {
+        assert_eq!(felt!(0, 31).invert_or_zero(), felt!(0, 31));
+        for value in 1..31u32 {
+            let x = felt!(value, 31);
+            assert_eq!(&x * x.invert_or_zero(), felt!(1, 31));
+            assert_eq!(x.invert_or_zero(), felt!(1, 31) / &x);
+        }
+
+        // p - 2 is zero in the field of 2
+        assert_eq!(felt!(0, 2).invert_or_zero(), felt!(0, 2));
+        assert_eq!(felt!(1, 2).invert_or_zero(), felt!(1, 2));
+    }
+
+    #[test]
+    fn test_multiplicative_order() {
+        // 3 is a primitive root modulo 7, 2 is a square so its order divides 3
+        assert_eq!(felt!(3, 7).multiplicative_order(), Some(6));
+        assert_eq!(felt!(2, 7).multiplicative_order(), Some(3));
+        assert_eq!(felt!(6, 7).multiplicative_order(), Some(2));
+        assert_eq!(felt!(1, 7).multiplicative_order(), Some(1));
+        assert_eq!(felt!(0, 7).multiplicative_order(), None);
+
+        // 5 is a primitive root modulo 223
+        assert_eq!(felt!(5, 223).multiplicative_order(), Some(222));
+    }
+
+    #[test]
+    fn test_from_wide_bytes() {
+        let prime = BigUint::parse_bytes(
+            b"fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+            16,
+        )
+        .unwrap();
+        let prime_u256 = U256::from_big_endian(&prime.to_bytes_be());
+
+        let wide: Vec<u8> = (0..64u8).map(|i| i.wrapping_mul(37).wrapping_add(200)).collect();
+        let felt = Felt::from_wide_bytes(&wide, prime_u256);
+        assert_eq!(felt.inner(), &(BigUint::from_bytes_be(&wide) % &prime));
+        assert_eq!(felt.prime(), &prime);
+
+        let expected = BigUint::from_bytes_be(&[0xff; 64]) % BigUint::from(223u32);
+        assert_eq!(
+            Felt::from_wide_bytes(&[0xff; 64], U256::from(223)),
+            Felt::new(expected, BigUint::from(223u32))
+        );
+        assert_eq!(Felt::from_wide_bytes(&[], U256::from(223)), felt!(0, 223));
+    }
+
+    #[test]
+    #[should_panic(expected = "Prime must not be zero")]
+    fn test_from_wide_bytes_zero_prime() {
+        let _ = Felt::from_wide_bytes(&[1, 2, 3], U256::zero());
+    }
+
+    #[test]
+    fn test_batch_mod_pow() {
+        let modulus = U256::from_str_radix(
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+            16,
+        )
+        .unwrap();
+        let exponent = modulus - U256::from(2);
+        let bases = [U256::from(0), U256::from(1), U256::from(3), U256::MAX, modulus - 1];
+
+        let to_big = |value: U256| {
+            let mut bytes = [0u8; 32];
+            value.to_big_endian(&mut bytes);
+            BigUint::from_bytes_be(&bytes)
+        };
+
+        let batch = pow::batch_mod_pow(&bases, exponent, modulus);
+        for (base, result) in bases.iter().zip(&batch) {
+            assert_eq!(*result, pow::mod_pow(*base, exponent, modulus));
+
+            // Matches BigUint's modpow
+            let expected = to_big(*base).modpow(&to_big(exponent), &to_big(modulus));
+            assert_eq!(to_big(*result), expected);
+        }
+
+        assert_eq!(pow::mod_pow(U256::from(5), U256::zero(), U256::from(7)), U256::one());
+        assert_eq!(pow::mod_pow(U256::from(5), U256::from(3), U256::one()), U256::zero());
+        assert!(pow::batch_mod_pow(&[], exponent, modulus).is_empty());
+    }
+
+    #[test]
+    fn test_prime_mod_4() {
+        let secp256k1_prime = BigUint::parse_bytes(
+            b"fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+            16,
+        )
+        .unwrap();
+        let felt = Felt::new(BigUint::from(5u32), secp256k1_prime);
+        assert!(felt.is_prime_3_mod_4());
+        assert!(!felt.is_prime_1_mod_4());
+
+        assert!(felt!(3, 13).is_prime_1_mod_4());
+        assert!(!felt!(3, 13).is_prime_3_mod_4());
+
+        // 2 is neither
+        assert!(!felt!(1, 2).is_prime_1_mod_4());
+        assert!(!felt!(1, 2).is_prime_3_mod_4());
+    }
+
+    #[test]
+    fn test_eval_poly() {
+        // 3 + 2x + 5x^2 over F_97
+        let coeffs = [felt!(3, 97), felt!(2, 97), felt!(5, 97)];
+        for x in 0u32..10 {
+            let expected = (3 + 2 * x + 5 * x * x) % 97;
+            assert_eq!(Felt::eval_poly(&coeffs, &felt!(x, 97)), felt!(expected, 97));
+        }
+
+        assert_eq!(Felt::eval_poly(&coeffs, &felt!(96, 97)), felt!(6, 97));
+        assert_eq!(Felt::eval_poly(&[], &felt!(5, 97)), felt!(0, 97));
+        assert_eq!(Felt::eval_poly(&[felt!(42, 97)], &felt!(5, 97)), felt!(42, 97));
+    }
+
+    #[test]
+    fn test_lagrange_interpolate() {
+        // 3 + 2x + 5x^2 over F_97
+        let coeffs = [felt!(3, 97), felt!(2, 97), felt!(5, 97)];
+        let points: Vec<(Felt, Felt)> = [1u32, 4, 9]
+            .into_iter()
+            .map(|x| (felt!(x, 97), Felt::eval_poly(&coeffs, &felt!(x, 97))))
+            .collect();
+
+        for x in [0u32, 1, 2, 50, 96] {
+            assert_eq!(
+                lagrange_interpolate(&points, &felt!(x, 97)).unwrap(),
+                Felt::eval_poly(&coeffs, &felt!(x, 97))
+            );
+        }
+
+        // Two points only determine a line, which misses the polynomial elsewhere
+        assert_ne!(lagrange_interpolate(&points[..2], &felt!(0, 97)).unwrap(), felt!(3, 97));
+        assert_eq!(lagrange_interpolate(&[], &felt!(5, 97)).unwrap(), felt!(0, 97));
+
+        let duplicated = [points[0].clone(), points[1].clone(), points[0].clone()];
+        assert_eq!(
+            lagrange_interpolate(&duplicated, &felt!(0, 97)),
+            Err(element::FieldError::DuplicateX)
+        );
+    }
+
+    #[test]
+    fn test_is_primitive_root() {
+        // 3 generates F_7*, 2 only reaches {1, 2, 4}
+        assert!(felt!(3, 7).is_primitive_root());
+        assert!(!felt!(2, 7).is_primitive_root());
+
+        // Agrees with the order found by brute force
+        for prime in [2u32, 3, 13, 23, 97, 101] {
+            for value in 0..prime {
+                let felt = felt!(value, prime);
+                let expected = felt.multiplicative_order() == Some(u64::from(prime - 1));
+                assert_eq!(felt.is_primitive_root(), expected, "{value} mod {prime}");
+            }
+        }
+
+        // Every prime has phi(p - 1) primitive roots, 40 for 101
+        let roots = (0..101u32).filter(|&value| felt!(value, 101).is_primitive_root()).count();
+        assert_eq!(roots, 40);
+    }
+
+    #[test]
+    fn test_mod_pow_window() {
+        let prime = U256::from_str_radix(
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+            16,
+        )
+        .unwrap();
+        let base = U256::from(0xdead_beef_u64);
+
+        let exponents = [
+            U256::zero(),
+            U256::one(),
+            U256::from(15),
+            U256::from(16),
+            prime - 2,
+            U256::MAX,
+        ];
+        for exponent in exponents {
+            assert_eq!(
+                pow::mod_pow_window(base, exponent, prime),
+                pow::mod_pow(base, exponent, prime),
+                "{exponent}"
+            );
+        }
+        for modulus in [U256::one(), U256::from(7), U256::from(1u64 << 61) - 1] {
+            for exponent in 0u32..40 {
+                let exponent = U256::from(exponent);
+                assert_eq!(
+                    pow::mod_pow_window(base, exponent, modulus),
+                    pow::mod_pow(base, exponent, modulus)
+                );
+            }
+        }
+    }
+
+    /// Compares the exponentiations on 256 bit exponents, run with
+    /// `cargo test --release bench_mod_pow -- --ignored --nocapture`
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_mod_pow() {
+        let prime = U256::from_str_radix(
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+            16,
+        )
+        .unwrap();
+        let exponent = prime - 2;
+        let bases: Vec<U256> = (1u64..=1000).map(|i| U256::from(i * 0x9e37_79b9)).collect();
+
+        let now = std::time::Instant::now();
+        for &base in &bases {
+            std::hint::black_box(pow::mod_pow(base, exponent, prime));
+        }
+        println!("mod_pow: {:?}", now.elapsed());
+
+        let now = std::time::Instant::now();
+        for &base in &bases {
+            std::hint::black_box(pow::mod_pow_window(base, exponent, prime));
+        }
+        println!("mod_pow_window: {:?}", now.elapsed());
+
+        let now = std::time::Instant::now();
+        std::hint::black_box(pow::batch_mod_pow(&bases, exponent, prime));
+        println!("batch_mod_pow: {:?}", now.elapsed());
+    }
+
+    /// Times the field multiplications scalar multiplication is built from: through the
+    /// operators, which wrap their reduced result with `new_unchecked`, against rebuilding
+    /// every product with the checked `new`. Then times whole secp256k1 scalar
+    /// multiplications, which run that inner loop. Run with
+    /// `cargo test --release bench_new_unchecked -- --ignored --nocapture`
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_new_unchecked() {
+        use crate::elliptic_curve::secp256k1::Secp256k1Point;
+
+        let prime = BigUint::parse_bytes(
+            b"fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+            16,
+        )
+        .unwrap();
+        let factor = Felt::new(BigUint::from(0x9e37_79b9_u64), prime.clone());
+
+        let now = std::time::Instant::now();
+        let mut product = factor.clone();
+        for _ in 0..100_000 {
+            product = &product * &factor;
+        }
+        std::hint::black_box(product);
+        println!("operators (new_unchecked): {:?}", now.elapsed());
+
+        let now = std::time::Instant::now();
+        let mut product = factor.clone();
+        for _ in 0..100_000 {
+            product = Felt::new((product.inner() * factor.inner()) % &prime, prime.clone());
+        }
+        std::hint::black_box(product);
+        println!("checked new: {:?}", now.elapsed());
+
+        let scalar = BigUint::parse_bytes(
+            b"e9873d79c6d87dc0fb6a5778633389f4453213303da61f20bd67fc233aa33262",
+            16,
+        )
+        .unwrap();
+        let now = std::time::Instant::now();
+        for _ in 0..10 {
+            std::hint::black_box(Secp256k1Point::g() * &scalar);
+        }
+        println!("G * scalar: {:?} each", now.elapsed() / 10);
+    }
+
+    #[test]
+    fn test_with_parity() {
+        // 3 and -3 = 16 in F_19
+        let felt = felt!(3, 19);
+        assert_eq!(felt.with_parity(true), felt!(3, 19));
+        assert_eq!(felt.with_parity(false), felt!(16, 19));
+        assert_eq!(felt!(16, 19).with_parity(true), felt!(3, 19));
+        assert_eq!(felt!(16, 19).with_parity(false), felt!(16, 19));
+
+        // Both roots of a square, whichever sqrt returns
+        let root = felt!(5, 19).pow(2u32).sqrt().unwrap();
+        assert_eq!(root.with_parity(true), felt!(5, 19));
+        assert_eq!(root.with_parity(false), felt!(14, 19));
+
+        assert_eq!(felt!(0, 19).with_parity(true), felt!(0, 19));
+    }
+
+    #[test]
+    fn test_conditional_assign() {
+        let mut felt = felt!(3, 19);
+        felt.conditional_assign(&felt!(16, 19), subtle::Choice::from(0));
+        assert_eq!(felt, felt!(3, 19));
+
+        felt.conditional_assign(&felt!(16, 19), subtle::Choice::from(1));
+        assert_eq!(felt, felt!(16, 19));
+        assert_eq!(felt.prime(), &BigUint::from(19u32));
+
+        // Values of different byte lengths in a wide field
+        let prime = BigUint::from(2u32).pow(255u32) - BigUint::from(19u32);
+        let mut small = Felt::new(BigUint::from(7u32), prime.clone());
+        let large = Felt::new(&prime - BigUint::from(1u32), prime.clone());
+        small.conditional_assign(&large, subtle::Choice::from(1));
+        assert_eq!(small, large);
+        small.conditional_assign(&Felt::new(BigUint::from(0u32), prime), subtle::Choice::from(1));
+        assert_eq!(small.inner(), &BigUint::from(0u32));
+    }
+
+    #[test]
+    fn test_in_range() {
+        let prime = BigUint::from(223u32);
+        assert!(element::in_range(&BigUint::from(0u32), &prime));
+        assert!(element::in_range(&BigUint::from(222u32), &prime));
+        assert!(!element::in_range(&prime, &prime));
+        assert!(!element::in_range(&BigUint::from(224u32), &prime));
+        assert!(!element::in_range(&(BigUint::from(1u32) << 300u32), &prime));
+
+        // Matches what try_new and from_u256 accept
+        for value in [0u32, 100, 222, 223, 1000] {
+            let big = BigUint::from(value);
+            let in_range = element::in_range(&big, &prime);
+            assert_eq!(Felt::from_u256(U256::from(value), U256::from(223u32)).is_ok(), in_range);
+            match Felt::try_new(big.clone(), prime.clone()) {
+                Ok(felt) => assert!(in_range && felt == Felt::new(big, prime.clone())),
+                Err(error) => assert!(!in_range && error == element::FieldError::OutOfRange),
+            }
+        }
+    }
+
+    #[test]
+    fn test_mod_pow_bits() {
+        let prime = U256::from_str_radix(
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+            16,
+        )
+        .unwrap();
+        let base = U256::from(0xdead_beef_u64);
+
+        // 0b1011 is 11
+        assert_eq!(
+            pow::mod_pow_bits(base, &[true, false, true, true], prime),
+            pow::mod_pow(base, U256::from(11), prime)
+        );
+        assert_eq!(pow::mod_pow_bits(base, &[], prime), U256::one());
+        assert_eq!(pow::mod_pow_bits(base, &[false, false, true], prime), base);
+
+        for exponent in [U256::zero(), U256::one(), prime - 2, U256::MAX] {
+            let bits: Vec<bool> = (0..256).rev().map(|bit| exponent.bit(bit)).collect();
+            assert_eq!(
+                pow::mod_pow_bits(base, &bits, prime),
+                pow::mod_pow(base, exponent, prime),
+                "{exponent}"
+            );
+        }
+        assert_eq!(pow::mod_pow_bits(base, &[true], U256::one()), U256::zero());
+    }
 }
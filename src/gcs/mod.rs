@@ -0,0 +1,110 @@
+#![allow(unused)]
+use crate::helpers::hash::hash256_array;
+use color_eyre::eyre::{eyre, Result};
+
+/// Returns the hash of a serialized compact filter, `hash256(filter)`
+pub fn filter_hash(filter: &[u8]) -> [u8; 32] {
+    hash256_array(filter)
+}
+
+/// A BIP157 filter header, committing to a block's filter and every filter before it
+///
+/// Peers serve filter headers before filters, so a client that has the chain of headers
+/// from one peer can check the filters any other peer serves against it. Bytes are in
+/// internal order, reversed from how headers are usually displayed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FilterHeader([u8; 32]);
+
+impl FilterHeader {
+    /// The header before the genesis block's, all zeros
+    pub const GENESIS_PREV: Self = Self([0u8; 32]);
+
+    /// Computes the header of a filter, `hash256(filter_hash || prev_header)`
+    pub fn new(filter_hash: &[u8; 32], prev_header: &FilterHeader) -> Self {
+        let mut data = [0u8; 64];
+        data[..32].copy_from_slice(filter_hash);
+        data[32..].copy_from_slice(&prev_header.0);
+        Self(hash256_array(&data))
+    }
+
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+/// Checks that each header commits to its filter hash and the header before it
+///
+/// `filter_hashes[i]` is the hash of the filter `headers[i]` is for, and `prev_header`
+/// is the header right before the first, `FilterHeader::GENESIS_PREV` when the chain
+/// starts at the genesis block.
+///
+/// # Errors
+///
+/// Returns an error if the lengths differ or a header doesn't link to the one before it
+pub fn validate_filter_headers(
+    prev_header: &FilterHeader,
+    filter_hashes: &[[u8; 32]],
+    headers: &[FilterHeader],
+) -> Result<()> {
+    if filter_hashes.len() != headers.len() {
+        return Err(eyre!(
+            "Got {} filter hashes for {} headers",
+            filter_hashes.len(),
+            headers.len()
+        ));
+    }
+
+    let mut prev_header = prev_header;
+    for (height, (filter_hash, header)) in filter_hashes.iter().zip(headers).enumerate() {
+        if FilterHeader::new(filter_hash, prev_header) != *header {
+            return Err(eyre!("Filter header {height} doesn't link to the one before it"));
+        }
+        prev_header = header;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::bytes::ToBytesBigEndian;
+
+    #[test]
+    fn test_genesis_filter_header() {
+        // Basic filter of the testnet genesis block, from the BIP158 test vectors
+        let filter = "019dfca8".to_bytes_be();
+        let header = FilterHeader::new(&filter_hash(&filter), &FilterHeader::GENESIS_PREV);
+
+        let mut expected = "21584579b7eb08997773e5aeff3a7f932700042d0ed2a6129012b7d7ae81b750"
+            .to_bytes_be();
+        expected.reverse();
+        assert_eq!(header.as_bytes().to_vec(), expected);
+    }
+
+    #[test]
+    fn test_validate_filter_headers() {
+        let filter_hashes: Vec<[u8; 32]> = (0u8..5).map(|i| filter_hash(&[i])).collect();
+        let mut headers = Vec::new();
+        let mut prev_header = FilterHeader::GENESIS_PREV;
+        for filter_hash in &filter_hashes {
+            prev_header = FilterHeader::new(filter_hash, &prev_header);
+            headers.push(prev_header);
+        }
+
+        let genesis = FilterHeader::GENESIS_PREV;
+        assert!(validate_filter_headers(&genesis, &filter_hashes, &headers).is_ok());
+        assert!(validate_filter_headers(&headers[1], &filter_hashes[2..], &headers[2..]).is_ok());
+        assert!(validate_filter_headers(&genesis, &[], &[]).is_ok());
+
+        // Starting from the wrong header, with a missing filter or with headers swapped
+        assert!(validate_filter_headers(&headers[0], &filter_hashes, &headers).is_err());
+        assert!(validate_filter_headers(&genesis, &filter_hashes[1..], &headers).is_err());
+        headers.swap(2, 3);
+        assert!(validate_filter_headers(&genesis, &filter_hashes, &headers).is_err());
+    }
+}
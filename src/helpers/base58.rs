@@ -0,0 +1,107 @@
+use super::{
+    bytes::ct_bytes_eq,
+    hash::{Hasher, StandardHasher},
+};
+use color_eyre::eyre::{eyre, Result};
+use num_bigint::BigUint;
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Encodes bytes in Base58
+///
+/// Leading zero bytes would be lost when the data is read as a number, so each one
+/// is kept as a leading '1'.
+pub fn encode(data: &[u8]) -> String {
+    let leading_zeros = data.iter().take_while(|&&byte| byte == 0).count();
+    let digits = if leading_zeros == data.len() {
+        Vec::new()
+    } else {
+        BigUint::from_bytes_be(data).to_radix_be(58)
+    };
+
+    let mut encoded = "1".repeat(leading_zeros);
+    encoded.extend(digits.iter().map(|&digit| ALPHABET[digit as usize] as char));
+    encoded
+}
+
+/// Decodes a Base58 string
+///
+/// # Errors
+///
+/// Returns an error if the string has a character that is not in the alphabet
+pub fn decode(encoded: &str) -> Result<Vec<u8>> {
+    let digits = encoded
+        .bytes()
+        .map(|character| {
+            ALPHABET
+                .iter()
+                .position(|&letter| letter == character)
+                .ok_or_else(|| eyre!("Invalid Base58 character {:?}", character as char))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let leading_zeros = digits.iter().take_while(|&&digit| digit == 0).count();
+    let mut decoded = vec![0u8; leading_zeros];
+    if leading_zeros < digits.len() {
+        // Digits are below 58, so they fit in a byte
+        #[allow(clippy::cast_possible_truncation)]
+        let digits: Vec<u8> = digits.iter().map(|&digit| digit as u8).collect();
+        let value = BigUint::from_radix_be(&digits, 58).unwrap_or_default();
+        decoded.extend(value.to_bytes_be());
+    }
+
+    Ok(decoded)
+}
+
+/// Returns the Base58 checksum of a payload, the first four bytes of its hash256
+pub fn checksum(payload: &[u8]) -> [u8; 4] {
+    checksum_with(&StandardHasher, payload)
+}
+
+/// Returns the Base58 checksum of a payload, with hash256 from the given hasher
+pub fn checksum_with(hasher: &impl Hasher, payload: &[u8]) -> [u8; 4] {
+    let hash = hasher.hash256(payload);
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Encodes bytes in Base58, appending their checksum
+pub fn encode_check(data: &[u8]) -> String {
+    encode_check_with(&StandardHasher, data)
+}
+
+/// Encodes bytes in Base58, appending their checksum from the given hasher
+pub fn encode_check_with(hasher: &impl Hasher, data: &[u8]) -> String {
+    let mut payload = data.to_vec();
+    payload.extend_from_slice(&checksum_with(hasher, data));
+    encode(&payload)
+}
+
+/// Decodes a Base58 string and checks and strips its checksum
+///
+/// # Errors
+///
+/// Returns an error if the string is not valid Base58, is too short to have a checksum,
+/// or the checksum doesn't match
+pub fn decode_check(encoded: &str) -> Result<Vec<u8>> {
+    decode_check_with(&StandardHasher, encoded)
+}
+
+/// Decodes a Base58 string and checks and strips its checksum from the given hasher
+///
+/// # Errors
+///
+/// Returns an error if the string is not valid Base58, is too short to have a checksum,
+/// or the checksum doesn't match
+pub fn decode_check_with(hasher: &impl Hasher, encoded: &str) -> Result<Vec<u8>> {
+    let decoded = decode(encoded)?;
+    if decoded.len() < 4 {
+        return Err(eyre!("Base58 string is too short to have a checksum"));
+    }
+
+    let (payload, expected) = decoded.split_at(decoded.len() - 4);
+    if !ct_bytes_eq(&checksum_with(hasher, payload), expected) {
+        return Err(eyre!("Invalid Base58 checksum"));
+    }
+
+    Ok(payload.to_vec())
+}
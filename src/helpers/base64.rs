@@ -0,0 +1,63 @@
+use color_eyre::eyre::{eyre, Result};
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes bytes in padded Base64 with the standard alphabet
+pub fn encode(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |group, (i, &byte)| group | u32::from(byte) << (16 - 8 * i));
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(char::from(ALPHABET[(group >> (18 - 6 * i)) as usize & 0x3f]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Decodes a padded Base64 string with the standard alphabet
+///
+/// # Errors
+///
+/// Returns an error if the length is not a multiple of four, a character is not in the
+/// alphabet, or padding shows up anywhere but the end
+pub fn decode(encoded: &str) -> Result<Vec<u8>> {
+    let encoded = encoded.as_bytes();
+    if !encoded.len().is_multiple_of(4) {
+        return Err(eyre!("Base64 string length must be a multiple of four"));
+    }
+
+    let mut decoded = Vec::with_capacity(encoded.len() / 4 * 3);
+    for (index, chunk) in encoded.chunks(4).enumerate() {
+        let is_last = index == encoded.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|&&character| character == b'=').count();
+        if padding > 2 || (padding > 0 && !is_last) {
+            return Err(eyre!("Invalid Base64 padding"));
+        }
+
+        let mut group = 0u32;
+        for (i, &character) in chunk[..4 - padding].iter().enumerate() {
+            let value = ALPHABET
+                .iter()
+                .position(|&letter| letter == character)
+                .ok_or_else(|| eyre!("Invalid Base64 character {:?}", character as char))?;
+            // Positions in a 64 character set
+            #[allow(clippy::cast_possible_truncation)]
+            let value = value as u32;
+            group |= value << (18 - 6 * i);
+        }
+
+        // The group holds three bytes, one for each character past the first
+        #[allow(clippy::cast_possible_truncation)]
+        decoded.extend((0..3 - padding).map(|i| (group >> (16 - 8 * i)) as u8));
+    }
+
+    Ok(decoded)
+}
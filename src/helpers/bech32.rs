@@ -0,0 +1,260 @@
+use std::fmt::Display;
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [0x3b6a_57b2, 0x2650_8e6d, 0x1ea1_19fa, 0x3d42_33dd, 0x2a14_62b3];
+
+/// Checksum flavour, BIP173 bech32 for witness version 0 and BIP350 bech32m for the rest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    Bech32,
+    Bech32m,
+}
+
+impl Variant {
+    fn constant(self) -> u32 {
+        match self {
+            Self::Bech32 => 1,
+            Self::Bech32m => 0x2bc8_30a3,
+        }
+    }
+
+    /// Returns the variant a segwit address of the given witness version must use
+    pub fn for_witness_version(version: u8) -> Self {
+        if version == 0 {
+            Self::Bech32
+        } else {
+            Self::Bech32m
+        }
+    }
+}
+
+/// Reasons a bech32 string or segwit address can fail to decode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Bech32Error {
+    InvalidLength,
+    MixedCase,
+    MissingSeparator,
+    InvalidCharacter(char),
+    InvalidValue(u8),
+    InvalidChecksum,
+    InvalidPadding,
+    InvalidWitnessVersion(u8),
+    InvalidProgramLength(usize),
+    WrongVariant,
+}
+
+impl Display for Bech32Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidLength => write!(f, "Invalid bech32 string length"),
+            Self::MixedCase => write!(f, "Bech32 string mixes upper and lower case"),
+            Self::MissingSeparator => write!(f, "Bech32 string has no separator"),
+            Self::InvalidCharacter(character) => {
+                write!(f, "Invalid bech32 character {character:?}")
+            }
+            Self::InvalidValue(value) => {
+                write!(f, "Bech32 value {value} is too wide for its group")
+            }
+            Self::InvalidChecksum => write!(f, "Invalid bech32 checksum"),
+            Self::InvalidPadding => write!(f, "Invalid padding in bech32 data"),
+            Self::InvalidWitnessVersion(version) => write!(f, "Invalid witness version {version}"),
+            Self::InvalidProgramLength(length) => {
+                write!(f, "Invalid witness program length {length}")
+            }
+            Self::WrongVariant => write!(f, "Checksum variant doesn't match the witness version"),
+        }
+    }
+}
+
+impl std::error::Error for Bech32Error {}
+
+fn polymod(values: impl IntoIterator<Item = u8>) -> u32 {
+    values.into_iter().fold(1, |checksum, value| {
+        let top = checksum >> 25;
+        let checksum = ((checksum & 0x01ff_ffff) << 5) ^ u32::from(value);
+        GENERATOR
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (top >> i) & 1 == 1)
+            .fold(checksum, |checksum, (_, generator)| checksum ^ generator)
+    })
+}
+
+/// High bits of every character, a zero, then the low bits of every character
+fn expand_hrp(hrp: &str) -> Vec<u8> {
+    let mut expanded: Vec<u8> = hrp.bytes().map(|byte| byte >> 5).collect();
+    expanded.push(0);
+    expanded.extend(hrp.bytes().map(|byte| byte & 0x1f));
+    expanded
+}
+
+/// Regroups bits, e.g. bytes into 5 bit groups and back
+///
+/// When `pad` is set leftover bits are padded with zeros, otherwise they have to be
+/// fewer than `from` and all zero.
+///
+/// # Errors
+///
+/// Returns an error if a value doesn't fit in `from` bits or the padding is invalid
+pub fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, Bech32Error> {
+    let mut accumulator = 0u32;
+    let mut bits = 0;
+    let mask = (1 << to) - 1;
+    let mut converted = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+
+    for &value in data {
+        if u32::from(value) >> from != 0 {
+            return Err(Bech32Error::InvalidValue(value));
+        }
+
+        accumulator = (accumulator << from) | u32::from(value);
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            // Masked to `to` bits, which is at most 8
+            #[allow(clippy::cast_possible_truncation)]
+            converted.push(((accumulator >> bits) & mask) as u8);
+        }
+    }
+
+    if pad {
+        if bits > 0 {
+            #[allow(clippy::cast_possible_truncation)]
+            converted.push(((accumulator << (to - bits)) & mask) as u8);
+        }
+    } else if bits >= from || (accumulator << (to - bits)) & mask != 0 {
+        return Err(Bech32Error::InvalidPadding);
+    }
+
+    Ok(converted)
+}
+
+/// Encodes 5 bit values with the human readable part and a checksum
+///
+/// # Errors
+///
+/// Returns an error if a value doesn't fit in 5 bits
+pub fn encode(hrp: &str, data: &[u8], variant: Variant) -> Result<String, Bech32Error> {
+    if let Some(&value) = data.iter().find(|&&value| value >= 32) {
+        return Err(Bech32Error::InvalidValue(value));
+    }
+
+    let hrp = hrp.to_lowercase();
+
+    let mut values = expand_hrp(&hrp);
+    values.extend_from_slice(data);
+    values.extend_from_slice(&[0; 6]);
+    let checksum = polymod(values) ^ variant.constant();
+
+    let mut encoded = hrp;
+    encoded.push('1');
+    encoded.extend(data.iter().map(|&value| CHARSET[usize::from(value)] as char));
+    encoded.extend((0..6).map(|i| CHARSET[((checksum >> (5 * (5 - i))) & 0x1f) as usize] as char));
+    Ok(encoded)
+}
+
+/// Decodes a bech32 or bech32m string into its human readable part and 5 bit values
+///
+/// # Errors
+///
+/// Returns an error if the string is malformed or the checksum is invalid for both variants
+pub fn decode(encoded: &str) -> Result<(String, Vec<u8>, Variant), Bech32Error> {
+    if encoded.len() > 90 {
+        return Err(Bech32Error::InvalidLength);
+    }
+    if encoded.chars().any(char::is_lowercase) && encoded.chars().any(char::is_uppercase) {
+        return Err(Bech32Error::MixedCase);
+    }
+
+    let encoded = encoded.to_lowercase();
+    let separator = encoded.rfind('1').ok_or(Bech32Error::MissingSeparator)?;
+    let (hrp, data) = (&encoded[..separator], &encoded[separator + 1..]);
+    if hrp.is_empty() || data.len() < 6 {
+        return Err(Bech32Error::InvalidLength);
+    }
+    if let Some(character) = hrp.chars().find(|&c| !(33..=126).contains(&u32::from(c))) {
+        return Err(Bech32Error::InvalidCharacter(character));
+    }
+
+    let data = data
+        .chars()
+        .map(|character| {
+            CHARSET
+                .iter()
+                .position(|&letter| char::from(letter) == character)
+                .ok_or(Bech32Error::InvalidCharacter(character))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // Positions in a 32 character set
+    #[allow(clippy::cast_possible_truncation)]
+    let data: Vec<u8> = data.into_iter().map(|value| value as u8).collect();
+
+    let mut values = expand_hrp(hrp);
+    values.extend_from_slice(&data);
+    let variant = match polymod(values) {
+        constant if constant == Variant::Bech32.constant() => Variant::Bech32,
+        constant if constant == Variant::Bech32m.constant() => Variant::Bech32m,
+        _ => return Err(Bech32Error::InvalidChecksum),
+    };
+
+    Ok((hrp.to_string(), data[..data.len() - 6].to_vec(), variant))
+}
+
+/// Returns the lowercase form of a valid bech32 or bech32m string
+///
+/// BIP173 allows either case but not both, and lowercase is the one to store and
+/// compare, e.g. an address typed in uppercase to fit a QR code's alphanumeric mode.
+///
+/// # Errors
+///
+/// Returns an error if the string doesn't decode, which includes mixing cases
+pub fn to_canonical(s: &str) -> Result<String, Bech32Error> {
+    decode(s)?;
+    Ok(s.to_lowercase())
+}
+
+/// Encodes a segwit address, picking bech32 or bech32m from the witness version
+///
+/// # Errors
+///
+/// Returns an error if the version is above 16 or the program length is invalid for it
+pub fn encode_segwit(hrp: &str, version: u8, program: &[u8]) -> Result<String, Bech32Error> {
+    validate_program(version, program)?;
+
+    let mut data = vec![version];
+    data.extend(convert_bits(program, 8, 5, true)?);
+    encode(hrp, &data, Variant::for_witness_version(version))
+}
+
+/// Decodes a segwit address into its witness version and program
+///
+/// Version 0 has to use bech32 and a 20 or 32 byte program, later versions have to
+/// use bech32m and a program between 2 and 40 bytes.
+///
+/// # Errors
+///
+/// Returns an error if the address is not valid bech32, the version or program length
+/// is invalid, or the checksum variant doesn't match the version
+pub fn decode_segwit(address: &str) -> Result<(u8, Vec<u8>), Bech32Error> {
+    let (_, data, variant) = decode(address)?;
+    let (&version, data) = data.split_first().ok_or(Bech32Error::InvalidLength)?;
+
+    let program = convert_bits(data, 5, 8, false)?;
+    validate_program(version, &program)?;
+    if variant != Variant::for_witness_version(version) {
+        return Err(Bech32Error::WrongVariant);
+    }
+
+    Ok((version, program))
+}
+
+fn validate_program(version: u8, program: &[u8]) -> Result<(), Bech32Error> {
+    if version > 16 {
+        return Err(Bech32Error::InvalidWitnessVersion(version));
+    }
+    if !(2..=40).contains(&program.len()) || (version == 0 && ![20, 32].contains(&program.len()))
+    {
+        return Err(Bech32Error::InvalidProgramLength(program.len()));
+    }
+    Ok(())
+}
@@ -1,3 +1,5 @@
+use num_bigint::BigUint;
+
 pub trait ToBytesBigEndian {
     fn to_bytes_be(&self) -> Vec<u8>;
 }
@@ -25,3 +27,42 @@ impl ToBytesBigEndian for &str {
             .collect()
     }
 }
+
+/// Serializes a `BigUint` as exactly 32 big endian bytes, padded from the left
+///
+/// # Panics
+///
+/// Panics if the value doesn't fit in 32 bytes
+pub fn to_32_bytes_be(value: &BigUint) -> [u8; 32] {
+    let bytes = value.to_bytes_be();
+    assert!(bytes.len() <= 32, "Value doesn't fit in 32 bytes");
+
+    let mut padded = [0u8; 32];
+    padded[32 - bytes.len()..].copy_from_slice(&bytes);
+    padded
+}
+
+/// Compares two byte slices without returning early at the first difference
+///
+/// Only the lengths are compared in variable time, so checksums and other values derived
+/// from secrets can be checked without leaking where they differ.
+pub fn ct_bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let difference = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    std::hint::black_box(difference) == 0
+}
+
+/// Encodes a Bitcoin variable length integer, see `Reader::read_varint`
+#[allow(clippy::cast_possible_truncation)]
+pub fn encode_varint(value: u64) -> Vec<u8> {
+    // Every branch only truncates values that fit
+    match value {
+        0..=0xfc => vec![value as u8],
+        0xfd..=0xffff => [&[0xfd][..], &(value as u16).to_le_bytes()].concat(),
+        0x1_0000..=0xffff_ffff => [&[0xfe][..], &(value as u32).to_le_bytes()].concat(),
+        _ => [&[0xff][..], &value.to_le_bytes()].concat(),
+    }
+}
@@ -1,7 +1,103 @@
 use super::bytes::ToBytesBigEndian;
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+use std::sync::LazyLock;
 
 /// Two rounds of SHA256.
 pub fn hash256(data: &[u8]) -> Vec<u8> {
     let first_round = sha256::digest(data).to_bytes_be();
     sha256::digest(first_round.as_slice()).to_bytes_be()
 }
+
+/// SHA256 followed by RIPEMD160, used for addresses.
+pub fn hash160(data: &[u8]) -> Vec<u8> {
+    let sha = Sha256::digest(data);
+    Ripemd160::digest(sha).to_vec()
+}
+
+/// `hash256` as a fixed size array, for txids, block hashes and other 32 byte digests
+pub fn hash256_array(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(Sha256::digest(data)).into()
+}
+
+/// `hash160` as a fixed size array, for key and script hashes
+pub fn hash160_array(data: &[u8]) -> [u8; 20] {
+    Ripemd160::digest(Sha256::digest(data)).into()
+}
+
+/// The hash functions addresses and checksums are built from
+///
+/// The free functions above are the real thing. Code that takes a `Hasher` can be
+/// handed a mock instead, so tests can check how a digest is used without depending
+/// on what it is.
+pub trait Hasher {
+    /// Two rounds of SHA256
+    fn hash256(&self, data: &[u8]) -> [u8; 32];
+
+    /// SHA256 followed by RIPEMD160
+    fn hash160(&self, data: &[u8]) -> [u8; 20];
+}
+
+/// The `Hasher` backed by the real SHA256 and RIPEMD160
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StandardHasher;
+
+impl Hasher for StandardHasher {
+    fn hash256(&self, data: &[u8]) -> [u8; 32] {
+        hash256_array(data)
+    }
+
+    fn hash160(&self, data: &[u8]) -> [u8; 20] {
+        hash160_array(data)
+    }
+}
+
+/// Tagged hash from BIP340: SHA256(SHA256(tag) || SHA256(tag) || data)
+pub fn tagged_hash(tag: &str, data: &[u8]) -> [u8; 32] {
+    let tag_hash = Sha256::digest(tag.as_bytes());
+
+    let mut hasher = Sha256::new();
+    hasher.update(tag_hash);
+    hasher.update(tag_hash);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+/// Tagged hasher that keeps the SHA256 state after the tag prefix
+///
+/// The prefix is 64 bytes, exactly one SHA256 block, so every tagged hash with the
+/// same tag starts from the same midstate. Hashing with a `TaggedHasher` resumes
+/// from that state instead of hashing the tag and the prefix again.
+#[derive(Debug, Clone)]
+pub struct TaggedHasher {
+    midstate: Sha256,
+}
+
+impl TaggedHasher {
+    /// Creates a hasher for the given tag
+    pub fn new(tag: &str) -> Self {
+        let tag_hash = Sha256::digest(tag.as_bytes());
+
+        let mut midstate = Sha256::new();
+        midstate.update(tag_hash);
+        midstate.update(tag_hash);
+        Self { midstate }
+    }
+
+    /// Returns the tagged hash of the data
+    pub fn hash(&self, data: &[u8]) -> [u8; 32] {
+        let mut hasher = self.midstate.clone();
+        hasher.update(data);
+        hasher.finalize().into()
+    }
+}
+
+/// Hashers for the tags BIP340 and BIP341 use, each built the first time it is used
+pub static BIP0340_AUX: LazyLock<TaggedHasher> = LazyLock::new(|| TaggedHasher::new("BIP0340/aux"));
+pub static BIP0340_NONCE: LazyLock<TaggedHasher> =
+    LazyLock::new(|| TaggedHasher::new("BIP0340/nonce"));
+pub static BIP0340_CHALLENGE: LazyLock<TaggedHasher> =
+    LazyLock::new(|| TaggedHasher::new("BIP0340/challenge"));
+pub static TAP_LEAF: LazyLock<TaggedHasher> = LazyLock::new(|| TaggedHasher::new("TapLeaf"));
+pub static TAP_BRANCH: LazyLock<TaggedHasher> = LazyLock::new(|| TaggedHasher::new("TapBranch"));
+pub static TAP_TWEAK: LazyLock<TaggedHasher> = LazyLock::new(|| TaggedHasher::new("TapTweak"));
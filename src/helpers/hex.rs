@@ -0,0 +1,78 @@
+use std::fmt::{Display, Write};
+
+/// Reasons a hex string or hash can fail to parse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A hash that is not 64 bytes long, the length is in bytes
+    InvalidLength(usize),
+    /// Hex that doesn't make up whole bytes
+    OddLength(usize),
+    InvalidCharacter(char),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidLength(length) => {
+                write!(f, "Hash must be 64 hex characters, got {length} bytes")
+            }
+            Self::OddLength(length) => write!(f, "Hex must have an even length, got {length}"),
+            Self::InvalidCharacter(character) => write!(f, "Invalid hex character {character:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses a block hash or txid the way explorers and RPCs display it
+///
+/// Hashes are displayed as little endian numbers, so the bytes are reversed from the
+/// order they are hashed and serialized in. The result is in that internal order.
+///
+/// # Errors
+///
+/// Returns an error if the string is not 64 hex characters
+pub fn parse_hash_display(s: &str) -> Result<[u8; 32], ParseError> {
+    if s.len() != 64 {
+        return Err(ParseError::InvalidLength(s.len()));
+    }
+
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&decode(s)?);
+    hash.reverse();
+    Ok(hash)
+}
+
+/// Decodes hex, upper or lower case, into bytes in the order they are written
+///
+/// # Errors
+///
+/// Returns an error if there is a non hex character or an odd number of digits
+pub fn decode(s: &str) -> Result<Vec<u8>, ParseError> {
+    if let Some(character) = s.chars().find(|character| !character.is_ascii_hexdigit()) {
+        return Err(ParseError::InvalidCharacter(character));
+    }
+    if !s.len().is_multiple_of(2) {
+        return Err(ParseError::OddLength(s.len()));
+    }
+
+    // Every character is an ASCII hex digit and two of them always fit in a byte
+    #[allow(clippy::cast_possible_truncation)]
+    let digit = |character: u8| char::from(character).to_digit(16).unwrap_or_default() as u8;
+    Ok(s.as_bytes().chunks(2).map(|pair| digit(pair[0]) << 4 | digit(pair[1])).collect())
+}
+
+/// Formats a hash in internal byte order the way it is displayed, see `parse_hash_display`
+pub fn format_hash_display(hash: &[u8; 32]) -> String {
+    let mut reversed = *hash;
+    reversed.reverse();
+    encode(&reversed)
+}
+
+/// Encodes bytes as lowercase hex, in the order they are given
+pub fn encode(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::with_capacity(bytes.len() * 2), |mut encoded, byte| {
+        let _ = write!(encoded, "{byte:02x}");
+        encoded
+    })
+}
@@ -1,2 +1,279 @@
+#![allow(unused)]
+pub mod base58;
+pub mod base64;
+pub mod bech32;
 pub mod bytes;
 pub mod hash;
+pub mod hex;
+pub mod reader;
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        base58, base64,
+        bech32::{self, Bech32Error},
+        bytes::{ct_bytes_eq, encode_varint, ToBytesBigEndian},
+        hash::*,
+        hex::{self, format_hash_display, parse_hash_display, ParseError},
+        reader::Reader,
+    };
+    use primitive_types::U256;
+
+    #[test]
+    fn test_tagged_hasher() {
+        let expected = "e427b2e3d7a2f37bba1e9c7367c75fed331febe4d0f7a0f561f640ef89e94f1e".to_bytes_be();
+        assert_eq!(tagged_hash("TapLeaf", b"Programming Bitcoin!").to_vec(), expected);
+
+        for tag in ["BIP0340/challenge", "TapLeaf", "TapTweak"] {
+            let hasher = TaggedHasher::new(tag);
+            for message in [&b""[..], b"Programming Bitcoin!", &[0xab; 100]] {
+                assert_eq!(hasher.hash(message), tagged_hash(tag, message));
+            }
+        }
+    }
+
+    #[test]
+    fn test_base58_checksum() {
+        // Payload of the genesis block coinbase address
+        let payload = "0062e907b15cbf27d5425399ebf6f0fb50ebb88f18".to_bytes_be();
+        assert_eq!(base58::checksum(&payload), [0xc2, 0x9b, 0x7d, 0x93]);
+        assert_eq!(base58::encode_check(&payload), "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa");
+    }
+
+    #[test]
+    fn test_reader() {
+        let data = "01020003000000040000000000000005".to_bytes_be();
+        let mut reader = Reader::new(&data);
+
+        assert_eq!(reader.read_u8().unwrap(), 0x01);
+        assert_eq!(reader.read_u16_le().unwrap(), 0x0002);
+        assert_eq!(reader.read_u32_le().unwrap(), 3);
+        assert_eq!(reader.read_bytes(0).unwrap(), &[] as &[u8]);
+        assert_eq!(reader.position(), 7);
+        assert_eq!(reader.read_u64_le().unwrap(), 4);
+        assert_eq!(reader.read_u8().unwrap(), 0x05);
+        assert!(reader.is_empty());
+
+        let mut reader = Reader::new(&[0xff; 32]);
+        assert_eq!(reader.read_u256_be().unwrap(), U256::MAX);
+    }
+
+    #[test]
+    fn test_reader_truncated() {
+        let data = [0x01, 0x02, 0x03];
+
+        assert!(Reader::new(&[]).read_u8().is_err());
+        assert!(Reader::new(&data).read_u32_le().is_err());
+        assert!(Reader::new(&data).read_u64_le().is_err());
+        assert!(Reader::new(&data).read_u256_be().is_err());
+        assert!(Reader::new(&data).read_bytes(4).is_err());
+
+        // A failed read doesn't consume anything
+        let mut reader = Reader::new(&data);
+        assert!(reader.read_array::<4>().is_err());
+        assert_eq!(reader.remaining(), 3);
+        assert_eq!(reader.read_u16_le().unwrap(), 0x0201);
+    }
+
+    #[test]
+    fn test_ct_bytes_eq() {
+        let inputs: [&[u8]; 5] = [b"", b"abcd", b"abce", b"bbcd", b"abc"];
+        for a in inputs {
+            for b in inputs {
+                assert_eq!(ct_bytes_eq(a, b), a == b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_base58_decode() {
+        let payload = "0062e907b15cbf27d5425399ebf6f0fb50ebb88f18".to_bytes_be();
+        let address = "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa";
+
+        assert_eq!(base58::decode_check(address).unwrap(), payload);
+        for data in [&[][..], &[0, 0], &[0, 0, 1, 2, 255], &payload] {
+            assert_eq!(base58::decode(&base58::encode(data)).unwrap(), data);
+        }
+
+        // Last character changed
+        assert!(base58::decode_check("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb").is_err());
+        // 0, O, I and l are not in the alphabet
+        assert!(base58::decode("1A1zP1eP5QGefi2DMPTfTL5SLmv7Div0Na").is_err());
+        assert!(base58::decode_check("1111").is_err());
+    }
+
+    #[test]
+    fn test_bech32_decode_segwit() {
+        let (version, program) =
+            bech32::decode_segwit("BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T4").unwrap();
+        assert_eq!(version, 0);
+        assert_eq!(program, "751e76e8199196d454941c45d1b3a323f1433bd6".to_bytes_be());
+        assert_eq!(
+            bech32::encode_segwit("bc", version, &program).unwrap(),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+
+        let address = "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0";
+        let (version, program) = bech32::decode_segwit(address).unwrap();
+        assert_eq!(version, 1);
+        assert_eq!(
+            program,
+            "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798".to_bytes_be()
+        );
+        assert_eq!(bech32::encode_segwit("bc", version, &program).unwrap(), address);
+
+        // Version 0 with a 16 byte program
+        assert_eq!(
+            bech32::decode_segwit("BC1QR508D6QEJXTDG4Y5R3ZARVARYV98GJ9P"),
+            Err(Bech32Error::InvalidProgramLength(16))
+        );
+        // Version 1 with a bech32 checksum
+        let mut data = vec![1];
+        data.extend(bech32::convert_bits(&program, 8, 5, true).unwrap());
+        let wrong_variant = bech32::encode("bc", &data, bech32::Variant::Bech32).unwrap();
+        assert_eq!(bech32::decode_segwit(&wrong_variant), Err(Bech32Error::WrongVariant));
+        assert_eq!(
+            bech32::decode_segwit("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5"),
+            Err(Bech32Error::InvalidChecksum)
+        );
+
+        // Values are 5 bits, a byte slipped in unconverted is an error
+        assert_eq!(
+            bech32::encode("bc", &[1, 31, 32], bech32::Variant::Bech32m),
+            Err(Bech32Error::InvalidValue(32))
+        );
+        assert_eq!(
+            bech32::encode("bc", &[0xff], bech32::Variant::Bech32),
+            Err(Bech32Error::InvalidValue(0xff))
+        );
+        assert_eq!(bech32::convert_bits(&[1, 32], 5, 8, true), Err(Bech32Error::InvalidValue(32)));
+    }
+
+    #[test]
+    fn test_varint() {
+        let cases: [(u64, &str); 7] = [
+            (0, "00"),
+            (0xfc, "fc"),
+            (0xfd, "fdfd00"),
+            (0xffff, "fdffff"),
+            (0x1_0000, "fe00000100"),
+            (0xffff_ffff, "feffffffff"),
+            (0x1_0000_0000, "ff0000000001000000"),
+        ];
+
+        for (value, encoded) in cases {
+            let encoded = encoded.to_bytes_be();
+            assert_eq!(encode_varint(value), encoded);
+
+            let mut reader = Reader::new(&encoded);
+            assert_eq!(reader.read_varint().unwrap(), value);
+            assert!(reader.is_empty());
+        }
+
+        assert!(Reader::new(&[0xfe, 0x00, 0x01]).read_varint().is_err());
+
+        // Values that fit in a shorter encoding are rejected
+        for non_minimal in ["fdfc00", "fd0000", "feffff0000", "ffffffffff00000000"] {
+            let encoded = non_minimal.to_bytes_be();
+            assert!(Reader::new(&encoded).read_varint().is_err(), "{non_minimal}");
+        }
+    }
+
+    #[test]
+    fn test_base64() {
+        for (data, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"fooba", "Zm9vYmE="),
+            (b"foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(base64::encode(data), encoded);
+            assert_eq!(base64::decode(encoded).unwrap(), data);
+        }
+
+        assert!(base64::decode("Zm9").is_err());
+        assert!(base64::decode("Zm9v!A==").is_err());
+        assert!(base64::decode("Zg==Zm9v").is_err());
+        assert!(base64::decode("Z===").is_err());
+    }
+
+    #[test]
+    fn test_hash_display() {
+        // Genesis block, its header hashes to the reverse of the displayed hash
+        let displayed = "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f";
+        let header = "0100000000000000000000000000000000000000000000000000000000000000\
+            000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa\
+            4b1e5e4a29ab5f49ffff001d1dac2b7c"
+            .to_bytes_be();
+
+        let hash = parse_hash_display(displayed).unwrap();
+        assert_eq!(hash.to_vec(), hash256(&header));
+        assert_eq!(hash, hash256_array(&header));
+        assert_eq!(hash160_array(&header).to_vec(), hash160(&header));
+        assert_eq!(hash[0], 0x6f);
+        assert_eq!(format_hash_display(&hash), displayed);
+
+        // Upper case is accepted, formatting is always lower case
+        let upper = parse_hash_display(&displayed.to_uppercase()).unwrap();
+        assert_eq!(upper, hash);
+
+        assert_eq!(parse_hash_display(&displayed[2..]), Err(ParseError::InvalidLength(62)));
+        // The length is in bytes, which is what is checked
+        let multibyte = format!("{}é", &displayed[3..]);
+        assert_eq!(parse_hash_display(&multibyte), Err(ParseError::InvalidLength(63)));
+        let invalid = format!("{}zz", &displayed[2..]);
+        assert_eq!(parse_hash_display(&invalid), Err(ParseError::InvalidCharacter('z')));
+    }
+
+    #[test]
+    fn test_hex_decode() {
+        assert_eq!(hex::decode("00ff7Fa0"), Ok(vec![0x00, 0xff, 0x7f, 0xa0]));
+        assert_eq!(hex::decode(""), Ok(vec![]));
+        for bytes in [vec![0u8; 33], (0..=255).collect()] {
+            assert_eq!(hex::decode(&hex::encode(&bytes)), Ok(bytes));
+        }
+
+        assert_eq!(hex::decode("abc"), Err(ParseError::OddLength(3)));
+        assert_eq!(hex::decode("0g"), Err(ParseError::InvalidCharacter('g')));
+        assert_eq!(hex::decode("+1"), Err(ParseError::InvalidCharacter('+')));
+        assert_eq!(hex::decode("é0"), Err(ParseError::InvalidCharacter('é')));
+    }
+
+    #[test]
+    fn test_well_known_tag_hashers() {
+        let hashers: [(&str, &TaggedHasher); 6] = [
+            ("BIP0340/aux", &BIP0340_AUX),
+            ("BIP0340/nonce", &BIP0340_NONCE),
+            ("BIP0340/challenge", &BIP0340_CHALLENGE),
+            ("TapLeaf", &TAP_LEAF),
+            ("TapBranch", &TAP_BRANCH),
+            ("TapTweak", &TAP_TWEAK),
+        ];
+
+        for (tag, hasher) in hashers {
+            for message in [&b""[..], b"Programming Bitcoin!", &[0xab; 100]] {
+                assert_eq!(hasher.hash(message), tagged_hash(tag, message), "{tag}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_bech32_to_canonical() {
+        let address = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+        assert_eq!(bech32::to_canonical(&address.to_uppercase()).unwrap(), address);
+        assert_eq!(bech32::to_canonical(address).unwrap(), address);
+
+        let mixed = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kV8F3T4";
+        assert_eq!(bech32::to_canonical(mixed), Err(Bech32Error::MixedCase));
+        assert_eq!(bech32::decode(mixed), Err(Bech32Error::MixedCase));
+
+        // Uppercase with a broken checksum doesn't canonicalize either
+        assert_eq!(
+            bech32::to_canonical("BC1QW508D6QEJXTDG4Y5R3ZARVARY0C5XW7KV8F3T5"),
+            Err(Bech32Error::InvalidChecksum)
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use color_eyre::eyre::{eyre, Result};
+use primitive_types::U256;
+
+/// Reads values from a byte slice, returning an error instead of panicking when the
+/// input runs out
+#[derive(Debug, Clone)]
+pub struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    /// Returns the number of bytes read so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the number of bytes left to read
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.position
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Reads the next `length` bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are fewer than `length` bytes left
+    pub fn read_bytes(&mut self, length: usize) -> Result<&'a [u8]> {
+        if length > self.remaining() {
+            return Err(eyre!(
+                "Unexpected end of input, needed {length} bytes but {} are left",
+                self.remaining()
+            ));
+        }
+
+        let bytes = &self.data[self.position..self.position + length];
+        self.position += length;
+        Ok(bytes)
+    }
+
+    /// Reads the next `N` bytes into an array
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are fewer than `N` bytes left
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.read_bytes(N)?);
+        Ok(array)
+    }
+
+    /// Reads a single byte
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are no bytes left
+    pub fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    /// Reads a little endian `u16`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are fewer than 2 bytes left
+    pub fn read_u16_le(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.read_array()?))
+    }
+
+    /// Reads a little endian `u32`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are fewer than 4 bytes left
+    pub fn read_u32_le(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    /// Reads a little endian `u64`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are fewer than 8 bytes left
+    pub fn read_u64_le(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.read_array()?))
+    }
+
+    /// Reads a Bitcoin variable length integer
+    ///
+    /// Values below `0xfd` are a single byte, otherwise the prefix `0xfd`, `0xfe` or
+    /// `0xff` is followed by a little endian `u16`, `u32` or `u64`.
+    ///
+    /// Like Bitcoin Core, the encoding has to be minimal. Otherwise the same message
+    /// could be serialized in more than one way, and a transaction or block re-encoded
+    /// with `encode_varint` would hash differently from the bytes it was parsed from.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input ends before the integer does, or the value would
+    /// fit in a shorter encoding
+    pub fn read_varint(&mut self) -> Result<u64> {
+        let (value, minimum) = match self.read_u8()? {
+            0xfd => (u64::from(self.read_u16_le()?), 0xfd),
+            0xfe => (u64::from(self.read_u32_le()?), 0x1_0000),
+            0xff => (self.read_u64_le()?, 0x1_0000_0000),
+            value => return Ok(u64::from(value)),
+        };
+
+        if value < minimum {
+            return Err(eyre!("Non-minimal variable length integer encoding of {value}"));
+        }
+        Ok(value)
+    }
+
+    /// Reads a big endian 256 bit integer
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are fewer than 32 bytes left
+    pub fn read_u256_be(&mut self) -> Result<U256> {
+        Ok(U256::from_big_endian(self.read_bytes(32)?))
+    }
+}
@@ -6,14 +6,27 @@
 )]
 
 use crate::{elliptic_curve::private_key::PrivateKey};
-use elliptic_curve::secp256k1::Secp256k1Felt;
+use elliptic_curve::scalar::Scalar;
 
+pub mod amount;
+pub mod block;
+pub mod commitment;
 pub mod elliptic_curve;
 pub mod finite_fields;
+pub mod gcs;
 mod helpers;
+pub mod merkle;
+pub mod network;
+pub mod script;
+pub mod signing;
+pub mod sss;
+pub mod taproot;
+pub mod transaction;
+#[cfg(test)]
+mod testvectors;
 
 fn main() {
-    let secret = Secp256k1Felt::from_bytes("my secret".as_bytes());
+    let secret = Scalar::from_bytes("my secret".as_bytes());
 
     let wallet = PrivateKey::new(secret);
     let signature = wallet.sign_slice(b"Programming Bitcoin!");
@@ -23,7 +36,7 @@ fn main() {
 
     let now = std::time::Instant::now();
     let is_legit = wallet.verify_slice(b"Programming Bitcoin!", &signature);
-    println!("Should be legit: {}", is_legit);
+    println!("Should be legit: {is_legit}");
 
     println!("Time: {:?}", now.elapsed());
 }
@@ -0,0 +1,163 @@
+#![allow(unused)]
+use crate::helpers::hash::hash256_array;
+
+/// Hashes two children into their parent, `hash256(left || right)`
+///
+/// Hashes are taken as raw bytes, transaction ids have to be reversed from the way they
+/// are usually displayed before building a tree out of them.
+pub fn merkle_parent(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(left);
+    data[32..].copy_from_slice(right);
+    hash256_array(&data)
+}
+
+/// Returns the level above the given one
+///
+/// When the level has an odd number of hashes, the last one is paired with itself.
+pub fn merkle_parent_level(hashes: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    hashes
+        .chunks(2)
+        .map(|pair| merkle_parent(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+        .collect()
+}
+
+/// Returns the merkle root of the hashes
+///
+/// The root of a single hash is the hash itself, the root of no hashes is all zeros.
+pub fn merkle_root(hashes: &[[u8; 32]]) -> [u8; 32] {
+    let mut level = hashes.to_vec();
+    while level.len() > 1 {
+        level = merkle_parent_level(&level);
+    }
+    level.first().copied().unwrap_or_default()
+}
+
+/// Builds a merkle root from hashes that arrive one at a time
+///
+/// Only the left subtrees that are still waiting for a right sibling are kept, one per
+/// level, so memory grows with the height of the tree rather than its size.
+#[derive(Debug, Default, Clone)]
+pub struct MerkleAccumulator {
+    /// `pending[level]` is only meaningful when bit `level` of `count` is set
+    pending: Vec<[u8; 32]>,
+    count: u64,
+}
+
+impl MerkleAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of hashes pushed so far
+    pub fn len(&self) -> u64 {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Adds the next hash
+    ///
+    /// Complete subtrees are merged right away, like carrying when adding one to a
+    /// binary counter.
+    pub fn push(&mut self, hash: [u8; 32]) {
+        let mut hash = hash;
+        let mut level = 0;
+        while self.count & (1 << level) != 0 {
+            hash = merkle_parent(&self.pending[level], &hash);
+            level += 1;
+        }
+
+        if level == self.pending.len() {
+            self.pending.push(hash);
+        } else {
+            self.pending[level] = hash;
+        }
+        self.count += 1;
+    }
+
+    /// Returns the merkle root of the hashes pushed so far, matching `merkle_root`
+    pub fn root(&self) -> [u8; 32] {
+        if self.count == 0 {
+            return [0u8; 32];
+        }
+
+        // Start from the lowest subtree that is still waiting for a sibling
+        let mut count = self.count;
+        let mut level = count.trailing_zeros() as usize;
+        let mut hash = self.pending[level];
+
+        while count != 1 << level {
+            // The last hash of a level with an odd number of hashes is paired with itself,
+            // which completes the subtree and carries it up past the full subtrees before it
+            hash = merkle_parent(&hash, &hash);
+            count += 1 << level;
+            level += 1;
+
+            while count & (1 << level) == 0 {
+                hash = merkle_parent(&self.pending[level], &hash);
+                level += 1;
+            }
+        }
+
+        hash
+    }
+}
+
+fn to_hash(bytes: &[u8]) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(bytes);
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::bytes::ToBytesBigEndian;
+
+    fn hashes(count: u8) -> Vec<[u8; 32]> {
+        (0..count).map(|i| hash256_array(&[i])).collect()
+    }
+
+    #[test]
+    fn test_merkle_root() {
+        let hashes: Vec<[u8; 32]> = [
+            "c117ea8ec828342f4dfb0ad6bd140e03a50720ece40169ee38bdc15d9eb64cf5",
+            "c131474164b412e3406696da1ee20ab0fc9bf41c8f05fa8ceea7a08d672d7cc5",
+            "f391da6ecfeed1814efae39e7fcb3838ae0b02c02ae7d0a5848a66947c0727b0",
+            "3d238a92a94532b946c90e19c49351c763696cff3db400485b813aecb8a13181",
+            "10092f2633be5f3ce349bf9ddbde36caa3dd10dfa0ec8106bce23acbff637dae",
+            "7d37b3d54fa6a64869084bfd2e831309118b9e833610e6228adacdbd1b4ba161",
+            "8118a77e542892fe15ae3fc771a4abfd2f5d5d5997544c3487ac36b5c85170fc",
+            "dff6879848c2c9b62fe652720b8df5272093acfaa45a43cdb3696fe2466a3877",
+            "b825c0745f46ac58f7d3759e6dc535a1fec7820377f24d4c2c6ad2cc55c0cb59",
+            "95513952a04bd8992721e9b7e2937f1c04ba31e0469fbe615a78197f68f52b7c",
+            "2e6d722e5e4dbdf2447ddecc9f7dabb8e299bae921c99ad5b0184cd9eb8e5908",
+            "b13a750047bc0bdceb2473e5fe488c2596d7a7124b4e716fdd29b046ef99bbf0",
+        ]
+        .iter()
+        .map(|hash| to_hash(&hash.to_bytes_be()))
+        .collect();
+
+        let expected = "acbcab8bcc1af95d8d563b77d24c3d19b18f1486383d75a5085c4e86c86beed6";
+        assert_eq!(merkle_root(&hashes).to_vec(), expected.to_bytes_be());
+    }
+
+    #[test]
+    fn test_merkle_accumulator() {
+        assert_eq!(MerkleAccumulator::new().root(), merkle_root(&[]));
+
+        for count in 1..=40 {
+            let hashes = hashes(count);
+            let mut accumulator = MerkleAccumulator::new();
+            for hash in &hashes {
+                accumulator.push(*hash);
+            }
+
+            assert_eq!(accumulator.len(), u64::from(count));
+            assert_eq!(accumulator.root(), merkle_root(&hashes), "{count} hashes");
+        }
+    }
+}
@@ -0,0 +1,435 @@
+#![allow(unused)]
+use crate::helpers::{bytes::encode_varint, reader::Reader};
+use color_eyre::eyre::{eyre, Result};
+use std::net::{IpAddr, Ipv6Addr};
+
+/// Most addresses an `addr` message is allowed to carry
+pub const MAX_ADDR_ENTRIES: u64 = 1000;
+
+/// Most entries an `inv` or `getdata` message is allowed to carry
+pub const MAX_INV_ENTRIES: u64 = 50_000;
+
+/// A network address as it appears in `version` and `addr` messages
+///
+/// IPv4 addresses are sent as IPv4-mapped IPv6 addresses, `::ffff:a.b.c.d`, and
+/// mapped back when parsing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetAddr {
+    pub services: u64,
+    pub ip: IpAddr,
+    pub port: u16,
+}
+
+impl NetAddr {
+    /// Serializes the address, the port is big endian unlike everything else
+    pub fn serialize(&self) -> Vec<u8> {
+        let ip = match self.ip {
+            IpAddr::V4(ip) => ip.to_ipv6_mapped(),
+            IpAddr::V6(ip) => ip,
+        };
+
+        let mut serialized = Vec::with_capacity(26);
+        serialized.extend_from_slice(&self.services.to_le_bytes());
+        serialized.extend_from_slice(&ip.octets());
+        serialized.extend_from_slice(&self.port.to_be_bytes());
+        serialized
+    }
+
+    /// Reads an address
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input ends early
+    pub fn parse(reader: &mut Reader<'_>) -> Result<Self> {
+        let services = reader.read_u64_le()?;
+        let ip = Ipv6Addr::from(reader.read_array::<16>()?);
+        let ip = ip.to_ipv4_mapped().map_or(IpAddr::V6(ip), IpAddr::V4);
+        let port = u16::from_be_bytes(reader.read_array()?);
+
+        Ok(Self { services, ip, port })
+    }
+}
+
+/// A peer's address as gossiped in `addr` messages
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetAddress {
+    /// Unix time the peer was last seen
+    pub time: u32,
+    pub addr: NetAddr,
+}
+
+impl NetAddress {
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut serialized = self.time.to_le_bytes().to_vec();
+        serialized.extend(self.addr.serialize());
+        serialized
+    }
+
+    /// Reads an address
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input ends early
+    pub fn parse(reader: &mut Reader<'_>) -> Result<Self> {
+        Ok(Self {
+            time: reader.read_u32_le()?,
+            addr: NetAddr::parse(reader)?,
+        })
+    }
+}
+
+/// Payload of an `addr` message
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AddrMessage {
+    pub addresses: Vec<NetAddress>,
+}
+
+impl AddrMessage {
+    pub const COMMAND: &'static str = "addr";
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut serialized = encode_varint(self.addresses.len() as u64);
+        for address in &self.addresses {
+            serialized.extend(address.serialize());
+        }
+        serialized
+    }
+
+    /// Parses the payload of an `addr` message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload is truncated, has trailing bytes or has more than
+    /// `MAX_ADDR_ENTRIES` addresses
+    pub fn parse(payload: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(payload);
+        let count = read_count(&mut reader, MAX_ADDR_ENTRIES)?;
+
+        let addresses = (0..count)
+            .map(|_| NetAddress::parse(&mut reader))
+            .collect::<Result<_>>()?;
+        expect_end(&reader)?;
+
+        Ok(Self { addresses })
+    }
+}
+
+/// What an inventory vector refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InventoryType {
+    Error,
+    Tx,
+    Block,
+    FilteredBlock,
+    CompactBlock,
+    WitnessTx,
+    WitnessBlock,
+    /// Types this crate doesn't know of are kept so they can be passed along
+    Unknown(u32),
+}
+
+impl From<u32> for InventoryType {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => Self::Error,
+            1 => Self::Tx,
+            2 => Self::Block,
+            3 => Self::FilteredBlock,
+            4 => Self::CompactBlock,
+            0x4000_0001 => Self::WitnessTx,
+            0x4000_0002 => Self::WitnessBlock,
+            value => Self::Unknown(value),
+        }
+    }
+}
+
+impl From<InventoryType> for u32 {
+    fn from(kind: InventoryType) -> Self {
+        match kind {
+            InventoryType::Error => 0,
+            InventoryType::Tx => 1,
+            InventoryType::Block => 2,
+            InventoryType::FilteredBlock => 3,
+            InventoryType::CompactBlock => 4,
+            InventoryType::WitnessTx => 0x4000_0001,
+            InventoryType::WitnessBlock => 0x4000_0002,
+            InventoryType::Unknown(value) => value,
+        }
+    }
+}
+
+/// A typed hash, announcing or requesting a transaction or block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inventory {
+    pub kind: InventoryType,
+    /// Hash in internal byte order, reversed from how ids are usually displayed
+    pub hash: [u8; 32],
+}
+
+/// Payload of an `inv` message, announcing objects a peer has
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InvMessage {
+    pub inventory: Vec<Inventory>,
+}
+
+impl InvMessage {
+    pub const COMMAND: &'static str = "inv";
+
+    pub fn serialize(&self) -> Vec<u8> {
+        serialize_inventory(&self.inventory)
+    }
+
+    /// Parses the payload of an `inv` message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload is truncated, has trailing bytes or has more than
+    /// `MAX_INV_ENTRIES` entries
+    pub fn parse(payload: &[u8]) -> Result<Self> {
+        let inventory = parse_inventory(payload)?;
+        Ok(Self { inventory })
+    }
+}
+
+/// Payload of a `getdata` message, requesting objects announced with `inv`
+///
+/// It has the same layout as `inv`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GetDataMessage {
+    pub inventory: Vec<Inventory>,
+}
+
+impl GetDataMessage {
+    pub const COMMAND: &'static str = "getdata";
+
+    pub fn serialize(&self) -> Vec<u8> {
+        serialize_inventory(&self.inventory)
+    }
+
+    /// Parses the payload of a `getdata` message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload is truncated, has trailing bytes or has more than
+    /// `MAX_INV_ENTRIES` entries
+    pub fn parse(payload: &[u8]) -> Result<Self> {
+        let inventory = parse_inventory(payload)?;
+        Ok(Self { inventory })
+    }
+}
+
+/// Payload of a `ping` message, peers answer with a `pong` carrying the same nonce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PingMessage {
+    pub nonce: u64,
+}
+
+impl PingMessage {
+    pub const COMMAND: &'static str = "ping";
+
+    pub fn serialize(&self) -> Vec<u8> {
+        self.nonce.to_le_bytes().to_vec()
+    }
+
+    /// Parses the payload of a `ping` message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload is not exactly the 8 byte nonce
+    pub fn parse(payload: &[u8]) -> Result<Self> {
+        Ok(Self { nonce: parse_nonce(payload)? })
+    }
+
+    /// Returns the `pong` that answers this ping
+    pub fn pong(&self) -> PongMessage {
+        PongMessage { nonce: self.nonce }
+    }
+}
+
+/// Payload of a `pong` message, echoing the nonce of the `ping` it answers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PongMessage {
+    pub nonce: u64,
+}
+
+impl PongMessage {
+    pub const COMMAND: &'static str = "pong";
+
+    pub fn serialize(&self) -> Vec<u8> {
+        self.nonce.to_le_bytes().to_vec()
+    }
+
+    /// Parses the payload of a `pong` message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload is not exactly the 8 byte nonce
+    pub fn parse(payload: &[u8]) -> Result<Self> {
+        Ok(Self { nonce: parse_nonce(payload)? })
+    }
+}
+
+fn parse_nonce(payload: &[u8]) -> Result<u64> {
+    let mut reader = Reader::new(payload);
+    let nonce = reader.read_u64_le()?;
+    expect_end(&reader)?;
+    Ok(nonce)
+}
+
+fn serialize_inventory(inventory: &[Inventory]) -> Vec<u8> {
+    let mut serialized = encode_varint(inventory.len() as u64);
+    for entry in inventory {
+        serialized.extend_from_slice(&u32::from(entry.kind).to_le_bytes());
+        serialized.extend_from_slice(&entry.hash);
+    }
+    serialized
+}
+
+fn parse_inventory(payload: &[u8]) -> Result<Vec<Inventory>> {
+    let mut reader = Reader::new(payload);
+    let count = read_count(&mut reader, MAX_INV_ENTRIES)?;
+
+    let inventory = (0..count)
+        .map(|_| {
+            Ok(Inventory {
+                kind: InventoryType::from(reader.read_u32_le()?),
+                hash: reader.read_array()?,
+            })
+        })
+        .collect::<Result<_>>()?;
+    expect_end(&reader)?;
+
+    Ok(inventory)
+}
+
+/// Reads the entry count of a message, refusing counts above the limit before allocating
+fn read_count(reader: &mut Reader<'_>, limit: u64) -> Result<u64> {
+    let count = reader.read_varint()?;
+    if count > limit {
+        return Err(eyre!("Message has {count} entries, at most {limit} are allowed"));
+    }
+    Ok(count)
+}
+
+fn expect_end(reader: &Reader<'_>) -> Result<()> {
+    if reader.is_empty() {
+        Ok(())
+    } else {
+        Err(eyre!("Unexpected bytes after message payload"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::bytes::ToBytesBigEndian;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_inv_round_trip() {
+        let mut tx_hash = [0u8; 32];
+        tx_hash[0] = 0xab;
+        let mut block_hash = [0u8; 32];
+        block_hash[31] = 0xcd;
+
+        let message = InvMessage {
+            inventory: vec![
+                Inventory { kind: InventoryType::Tx, hash: tx_hash },
+                Inventory { kind: InventoryType::Block, hash: block_hash },
+            ],
+        };
+
+        let serialized = message.serialize();
+        assert_eq!(serialized.len(), 1 + 2 * 36);
+        assert_eq!(serialized[..5], [0x02, 0x01, 0x00, 0x00, 0x00]);
+        assert_eq!(serialized[37..41], [0x02, 0x00, 0x00, 0x00]);
+        assert_eq!(InvMessage::parse(&serialized).unwrap(), message);
+
+        // getdata has the same layout
+        let request = GetDataMessage { inventory: message.inventory.clone() };
+        assert_eq!(request.serialize(), serialized);
+        assert_eq!(GetDataMessage::parse(&serialized).unwrap(), request);
+
+        let unknown = InvMessage {
+            inventory: vec![Inventory { kind: InventoryType::Unknown(7), hash: tx_hash }],
+        };
+        assert_eq!(InvMessage::parse(&unknown.serialize()).unwrap(), unknown);
+    }
+
+    #[test]
+    fn test_addr_round_trip() {
+        let message = AddrMessage {
+            addresses: vec![NetAddress {
+                time: 0x6500_0000,
+                addr: NetAddr {
+                    services: 1,
+                    ip: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+                    port: 8333,
+                },
+            }],
+        };
+
+        let serialized = message.serialize();
+        let expected = "01000000650100000000000000\
+            00000000000000000000ffff0a000001208d";
+        assert_eq!(serialized, expected.to_bytes_be());
+        assert_eq!(AddrMessage::parse(&serialized).unwrap(), message);
+    }
+
+    #[test]
+    fn test_net_addr_round_trip() {
+        let ipv4 = NetAddr {
+            services: 0x0409,
+            ip: IpAddr::V4(Ipv4Addr::new(192, 168, 1, 2)),
+            port: 18333,
+        };
+        let serialized = ipv4.serialize();
+        let expected = "0904000000000000\
+            00000000000000000000ffffc0a80102479d";
+        assert_eq!(serialized, expected.to_bytes_be());
+        assert_eq!(NetAddr::parse(&mut Reader::new(&serialized)).unwrap(), ipv4);
+
+        let ipv6 = NetAddr {
+            services: 1,
+            ip: IpAddr::V6("2001:db8::1".parse().unwrap()),
+            port: 8333,
+        };
+        let serialized = ipv6.serialize();
+        assert_eq!(serialized[8..24], "20010db8000000000000000000000001".to_bytes_be());
+        assert_eq!(NetAddr::parse(&mut Reader::new(&serialized)).unwrap(), ipv6);
+
+        assert!(NetAddr::parse(&mut Reader::new(&serialized[..25])).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_payloads() {
+        let message = InvMessage {
+            inventory: vec![Inventory { kind: InventoryType::Tx, hash: [1; 32] }],
+        };
+        let serialized = message.serialize();
+
+        assert!(InvMessage::parse(&serialized[..serialized.len() - 1]).is_err());
+        assert!(InvMessage::parse(&[serialized.as_slice(), &[0]].concat()).is_err());
+        assert!(AddrMessage::parse(&encode_varint(MAX_ADDR_ENTRIES + 1)).is_err());
+        assert!(InvMessage::parse(&encode_varint(MAX_INV_ENTRIES + 1)).is_err());
+
+        // A count of 1 encoded as `fd 01 00` instead of `01`
+        let non_minimal = [&[0xfd, 0x01, 0x00], &serialized[1..]].concat();
+        assert!(InvMessage::parse(&non_minimal).is_err());
+    }
+
+    #[test]
+    fn test_ping_pong() {
+        let payload = "efbeadde00000000".to_bytes_be();
+        let received = PingMessage::parse(&payload).unwrap();
+        assert_eq!(received.nonce, 0xdead_beef);
+
+        let reply = received.pong();
+        assert_eq!(reply.nonce, received.nonce);
+        assert_eq!(reply.serialize(), payload);
+        assert_eq!(PongMessage::parse(&reply.serialize()).unwrap(), reply);
+
+        assert!(PingMessage::parse(&payload[..7]).is_err());
+        assert!(PongMessage::parse(&[payload.as_slice(), &[0]].concat()).is_err());
+    }
+}
@@ -0,0 +1,67 @@
+#![allow(unused)]
+use crate::{elliptic_curve::public_key::Network, helpers::bech32};
+use sha2::{Digest, Sha256};
+
+/// `OP_0`, the witness version 0 opcode
+const OP_0: u8 = 0x00;
+
+/// Returns the P2WSH output script of a witness script, `OP_0 <sha256(witness_script)>`
+///
+/// Unlike P2SH, the script is committed to with a single SHA-256 rather than hash160.
+/// There's no `Script` type yet, so scripts are passed around as their serialization.
+pub fn p2wsh(witness_script: &[u8]) -> Vec<u8> {
+    let mut script = vec![OP_0, 32];
+    script.extend_from_slice(&Sha256::digest(witness_script));
+    script
+}
+
+/// Returns the P2WSH address of a witness script on the given network
+///
+/// # Panics
+///
+/// Doesn't panic in practice, a 32 byte program is always valid for version 0
+pub fn p2wsh_address(witness_script: &[u8], network: Network) -> String {
+    let program = Sha256::digest(witness_script);
+    bech32::encode_segwit(network.bech32_hrp(), 0, &program).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::bytes::ToBytesBigEndian;
+
+    #[test]
+    fn test_p2wsh_address() {
+        // BIP173, <G> OP_CHECKSIG
+        let script = "210279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798ac"
+            .to_bytes_be();
+        assert_eq!(
+            p2wsh(&script),
+            "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262".to_bytes_be()
+        );
+        assert_eq!(
+            p2wsh_address(&script, Network::Mainnet),
+            "bc1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3qccfmv3"
+        );
+        assert_eq!(
+            p2wsh_address(&script, Network::Testnet),
+            "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7"
+        );
+
+        // 2-of-3 multisig of G, 2G and 3G, address from the BIP173 reference encoder
+        let multisig = "52\
+            210279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\
+            2102c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5\
+            2102f9308a019258c31049344f85f89d5229b531c845836f99b08601f113bce036f9\
+            53ae"
+            .to_bytes_be();
+        assert_eq!(
+            p2wsh_address(&multisig, Network::Mainnet),
+            "bc1qztp0l0rwc8846ardl02fkyrrx43p96j47scz8l7qz3vnfteqc4eqtfqwcm"
+        );
+        assert_eq!(
+            p2wsh_address(&multisig, Network::Testnet),
+            "tb1qztp0l0rwc8846ardl02fkyrrx43p96j47scz8l7qz3vnfteqc4equpkpz5"
+        );
+    }
+}
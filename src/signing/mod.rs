@@ -0,0 +1,205 @@
+#![allow(unused)]
+use crate::{
+    elliptic_curve::{
+        private_key::PrivateKey,
+        public_key::{Network, PublicKey},
+        scalar::Scalar,
+        signature::Signature,
+    },
+    helpers::{base64, bytes::encode_varint, hash::hash256_array},
+};
+use std::fmt::Display;
+
+/// Prefix that keeps a signed message from ever being a valid transaction
+const MESSAGE_MAGIC: &[u8] = b"\x18Bitcoin Signed Message:\n";
+
+/// Reasons a signed message can fail to verify
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignError {
+    InvalidBase64,
+    InvalidLength(usize),
+    InvalidHeader(u8),
+    InvalidSignature,
+    RecoveryFailed,
+}
+
+impl Display for SignError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidBase64 => write!(f, "Signature is not valid Base64"),
+            Self::InvalidLength(length) => {
+                write!(f, "Signature must be 65 bytes long, got {length}")
+            }
+            Self::InvalidHeader(header) => write!(f, "Invalid signature header byte {header}"),
+            Self::InvalidSignature => write!(f, "Invalid r or s in signature"),
+            Self::RecoveryFailed => write!(f, "No public key can be recovered from signature"),
+        }
+    }
+}
+
+impl std::error::Error for SignError {}
+
+/// Returns the hash a message is signed over
+///
+/// `hash256(magic || varint(length) || message)`, the way Bitcoin Core's `signmessage`
+/// computes it.
+pub fn message_hash(message: &str) -> [u8; 32] {
+    let mut data = MESSAGE_MAGIC.to_vec();
+    data.extend(encode_varint(message.len() as u64));
+    data.extend_from_slice(message.as_bytes());
+    hash256_array(&data)
+}
+
+/// Signs a message, returning the Base64 encoded compact signature
+///
+/// The signature is a header byte followed by `r || s`. The header is 27 plus the
+/// recovery id, plus 4 when the address uses the compressed key.
+pub fn sign_message(private_key: &PrivateKey, message: &str, compressed: bool) -> String {
+    let z = Scalar::from_bytes(&message_hash(message));
+    let (signature, recovery_id) = private_key.sign_recoverable(&z);
+
+    let mut bytes = vec![27 + recovery_id + if compressed { 4 } else { 0 }];
+    bytes.extend_from_slice(&signature.to_bytes());
+    base64::encode(&bytes)
+}
+
+/// Parses a Base64 encoded compact signature, as Electrum and Bitcoin Core produce them
+///
+/// Returns the signature, the recovery id and whether the signer's address uses the
+/// compressed public key, see `sign_message` for the header byte.
+///
+/// # Errors
+///
+/// Returns an error if the signature is not 65 bytes of Base64, the header is not between
+/// 27 and 34, or r or s is invalid
+pub fn parse_signature(b64: &str) -> Result<(Signature, u8, bool), SignError> {
+    let bytes = base64::decode(b64).map_err(|_| SignError::InvalidBase64)?;
+    let bytes: [u8; 65] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| SignError::InvalidLength(bytes.len()))?;
+
+    let header = bytes[0];
+    if !(27..=34).contains(&header) {
+        return Err(SignError::InvalidHeader(header));
+    }
+    let recovery_id = (header - 27) & 3;
+    let compressed = header >= 31;
+
+    let mut compact = [0u8; 64];
+    compact.copy_from_slice(&bytes[1..]);
+    let signature = Signature::from_bytes(&compact).map_err(|_| SignError::InvalidSignature)?;
+
+    Ok((signature, recovery_id, compressed))
+}
+
+/// Recovers the P2PKH address that signed a message
+///
+/// The address is derived from the public key the signature recovers to, compressed or
+/// not as the header says. A signature is valid for an address when the recovered one
+/// matches it, so callers compare the result against the address they expect.
+///
+/// # Errors
+///
+/// Returns an error if the signature doesn't parse with `parse_signature`, or no public
+/// key can be recovered from it
+pub fn recover_address(
+    message: &str,
+    signature_b64: &str,
+    network: Network,
+) -> Result<String, SignError> {
+    let (signature, recovery_id, compressed) = parse_signature(signature_b64)?;
+
+    let z = Scalar::from_bytes(&message_hash(message));
+    let point = signature
+        .recover(&z, recovery_id)
+        .map_err(|_| SignError::RecoveryFailed)?;
+
+    Ok(PublicKey::new(point, network).p2pkh_address(compressed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigUint;
+
+    #[test]
+    fn test_recover_address() {
+        // From the bitcoinjs-message README. Electrum and Bitcoin Core use the same format,
+        // the same message prefix and header byte, so it stands in for their signatures
+        let message = "This is an example of a signed message.";
+        let signature = "H9L5yLFjti0QTHhPyFrZCT1V/MMnBtXKmoiKDZ78NDBj\
+            ERki6ZTQZdSMCtkgoNmp17By9ItJr8o7ChX0XxY91nk=";
+        assert_eq!(
+            recover_address(message, signature, Network::Mainnet).unwrap(),
+            "1F3sAm6ZtwLAUnj7d38pGFxtP3RVEvtsbV"
+        );
+
+        // A different message recovers some other key
+        assert_ne!(
+            recover_address("This is another message.", signature, Network::Mainnet).unwrap(),
+            "1F3sAm6ZtwLAUnj7d38pGFxtP3RVEvtsbV"
+        );
+    }
+
+    #[test]
+    fn test_sign_message_round_trip() {
+        let private_key = PrivateKey::new(Scalar::new(BigUint::from(12345u32)));
+        let message = "Programming Bitcoin!";
+
+        for network in [Network::Mainnet, Network::Testnet] {
+            let public_key = PublicKey::new(private_key.public_key().clone(), network);
+            for compressed in [true, false] {
+                let signature = sign_message(&private_key, message, compressed);
+                assert_eq!(
+                    recover_address(message, &signature, network).unwrap(),
+                    public_key.p2pkh_address(compressed)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_recover_address_rejects_malformed_signatures() {
+        let private_key = PrivateKey::new(Scalar::new(BigUint::from(12345u32)));
+        let signature = base64::decode(&sign_message(&private_key, "message", true)).unwrap();
+
+        let recover = |encoded: &str| recover_address("message", encoded, Network::Mainnet);
+        assert_eq!(recover("not base64"), Err(SignError::InvalidBase64));
+        assert_eq!(
+            recover(&base64::encode(&signature[..64])),
+            Err(SignError::InvalidLength(64))
+        );
+        assert_eq!(
+            recover(&base64::encode(&[&[35], &signature[1..]].concat())),
+            Err(SignError::InvalidHeader(35))
+        );
+        assert_eq!(
+            recover(&base64::encode(&[&signature[..1], &[0; 64]].concat())),
+            Err(SignError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_parse_signature() {
+        // The signature from test_recover_address, header 31 is recovery id 0, compressed
+        let signature = "H9L5yLFjti0QTHhPyFrZCT1V/MMnBtXKmoiKDZ78NDBj\
+            ERki6ZTQZdSMCtkgoNmp17By9ItJr8o7ChX0XxY91nk=";
+        let (parsed, recovery_id, compressed) = parse_signature(signature).unwrap();
+        assert_eq!(recovery_id, 0);
+        assert!(compressed);
+
+        let bytes = base64::decode(signature).unwrap();
+        assert_eq!(parsed.to_bytes()[..], bytes[1..]);
+
+        // Every header from 27 to 34
+        let private_key = PrivateKey::new(Scalar::new(BigUint::from(12345u32)));
+        let mut bytes = base64::decode(&sign_message(&private_key, "message", false)).unwrap();
+        for header in 27..=34u8 {
+            bytes[0] = header;
+            let (_, recovery_id, compressed) = parse_signature(&base64::encode(&bytes)).unwrap();
+            assert_eq!(recovery_id, (header - 27) % 4);
+            assert_eq!(compressed, header >= 31);
+        }
+    }
+}
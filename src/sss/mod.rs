@@ -0,0 +1,129 @@
+#![allow(unused)]
+use crate::finite_fields::{
+    element::{FieldError, Felt},
+    interpolation::lagrange_interpolate,
+};
+use num_bigint::BigUint;
+use rand::RngCore;
+
+/// Splits a secret into `shares` shares, any `threshold` of which recover it
+///
+/// A random polynomial of degree `threshold - 1` is picked with the secret as its
+/// constant term, and share `x` is the polynomial evaluated at `x` for `x` in
+/// `1..=shares`. Arithmetic happens in the secret's field, so the prime must be
+/// larger than any secret being shared; secp256k1's field prime fits private keys.
+///
+/// # Panics
+///
+/// Panics if `threshold` is zero or larger than `shares`, or if `shares` isn't below the
+/// prime, since the x values would then repeat modulo the prime
+pub fn split(secret: Felt, threshold: u8, shares: u8) -> Vec<(u8, Felt)> {
+    assert!(threshold > 0, "Threshold must be at least 1");
+    assert!(threshold <= shares, "Threshold can't be larger than the number of shares");
+
+    let prime = secret.prime().clone();
+    assert!(BigUint::from(shares) < prime, "Number of shares must be below the prime");
+
+    let mut rng = rand::thread_rng();
+    let mut random_felt = || {
+        // 16 bytes more than the prime keep the bias from the reduction negligible
+        let mut bytes = vec![0u8; prime.to_bytes_be().len() + 16];
+        rng.fill_bytes(&mut bytes);
+        Felt::new(BigUint::from_bytes_be(&bytes) % &prime, prime.clone())
+    };
+
+    let mut coefficients = vec![secret];
+    for _ in 1..threshold {
+        coefficients.push(random_felt());
+    }
+
+    (1..=shares)
+        .map(|x| {
+            let y = Felt::eval_poly(&coefficients, &Felt::new(BigUint::from(x), prime.clone()));
+            (x, y)
+        })
+        .collect()
+}
+
+/// Recombines shares into the secret by Lagrange interpolation at 0
+///
+/// Fewer than `threshold` shares give an unrelated value rather than an error, since
+/// any set of points lies on some polynomial.
+///
+/// # Errors
+///
+/// Returns `FieldError::NoPoints` if no shares are given, `FieldError::OutOfRange` if an
+/// x value isn't below the prime and `FieldError::DuplicateX` if two shares have the same
+/// x value
+pub fn combine(shares: &[(u8, Felt)]) -> Result<Felt, FieldError> {
+    let (_, first) = shares.first().ok_or(FieldError::NoPoints)?;
+    let prime = first.prime().clone();
+    let points = shares
+        .iter()
+        .map(|(x, y)| Ok((Felt::try_new(BigUint::from(*x), prime.clone())?, y.clone())))
+        .collect::<Result<Vec<_>, FieldError>>()?;
+
+    lagrange_interpolate(&points, &Felt::new(BigUint::from(0u32), prime))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{combine, split};
+    use crate::{
+        elliptic_curve::secp256k1::Secp256k1Felt,
+        finite_fields::element::{FieldError, Felt},
+    };
+    use num_bigint::BigUint;
+
+    #[test]
+    fn test_split_combine() {
+        let secret = Felt::new(
+            BigUint::parse_bytes(
+                b"e9873d79c6d87dc0fb6a5778633389f4453213303da61f20bd67fc233aa33262",
+                16,
+            )
+            .unwrap(),
+            Secp256k1Felt::prime(),
+        );
+
+        let shares = split(secret.clone(), 3, 5);
+        assert_eq!(shares.len(), 5);
+
+        // Any 3 shares recover the secret
+        for i in 0..5 {
+            for j in i + 1..5 {
+                for k in j + 1..5 {
+                    let subset = [shares[i].clone(), shares[j].clone(), shares[k].clone()];
+                    assert_eq!(combine(&subset).unwrap(), secret);
+                }
+            }
+        }
+
+        // 2 shares don't
+        assert_ne!(combine(&shares[..2]).unwrap(), secret);
+        assert_eq!(combine(&shares).unwrap(), secret);
+
+        // The same share twice is an error, not a panic
+        assert_eq!(
+            combine(&[shares[0].clone(), shares[1].clone(), shares[0].clone()]),
+            Err(FieldError::DuplicateX)
+        );
+        assert_eq!(combine(&[]), Err(FieldError::NoPoints));
+
+        // Fields wider than 256 bits work the same
+        let wide_prime = (BigUint::from(1u32) << 521u32) - BigUint::from(1u32);
+        let wide_secret = Felt::new(BigUint::from(1u32) << 400u32, wide_prime);
+        let wide_shares = split(wide_secret.clone(), 2, 3);
+        assert_eq!(combine(&wide_shares[1..]).unwrap(), wide_secret);
+
+        // In a tiny field an x value can be out of range
+        let small = |value: u32| Felt::new(BigUint::from(value), BigUint::from(5u32));
+        assert_eq!(combine(&[(1, small(2)), (6, small(3))]), Err(FieldError::OutOfRange));
+    }
+
+    #[test]
+    #[should_panic(expected = "Number of shares must be below the prime")]
+    fn test_split_more_shares_than_field() {
+        let _ = split(Felt::new(BigUint::from(3u32), BigUint::from(5u32)), 2, 5);
+    }
+}
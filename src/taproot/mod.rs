@@ -0,0 +1,505 @@
+#![allow(unused)]
+use crate::{
+    elliptic_curve::{schnorr::is_valid_xonly, secp256k1::Secp256k1Point},
+    helpers::{
+        bytes::encode_varint,
+        hash::{TAP_BRANCH, TAP_LEAF, TAP_TWEAK},
+    },
+};
+use num_bigint::BigUint;
+use std::fmt::Display;
+
+/// Leaf version of BIP342 tapscript
+pub const TAPSCRIPT_LEAF_VERSION: u8 = 0xc0;
+
+/// Most hashes a merkle path can have, a tree is at most 128 levels deep
+pub const MAX_MERKLE_PATH_LENGTH: usize = 128;
+
+/// Reasons a control block can fail to parse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaprootError {
+    /// Not `33 + 32 * m` bytes with `m` at most `MAX_MERKLE_PATH_LENGTH`
+    InvalidControlBlockLength(usize),
+    /// The internal key is not a valid x-only public key
+    InvalidInternalKey,
+    /// The tweak is not below the group order or the tweaked key is the identity
+    InvalidTweak,
+    /// A leaf is deeper than `MAX_MERKLE_PATH_LENGTH`
+    TreeTooDeep,
+}
+
+impl Display for TaprootError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidControlBlockLength(length) => {
+                write!(f, "Control block must be 33 + 32 * m bytes long, got {length}")
+            }
+            Self::InvalidInternalKey => write!(f, "Invalid internal key in control block"),
+            Self::InvalidTweak => write!(f, "Tweaking the internal key failed"),
+            Self::TreeTooDeep => {
+                write!(f, "Taproot tree is deeper than {MAX_MERKLE_PATH_LENGTH} levels")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TaprootError {}
+
+/// The control block a BIP341 script path spend puts last in its witness
+///
+/// It holds what the verifier needs to recompute the output key from the script: the
+/// leaf version, the parity of the output key's y, the internal key and the hashes
+/// along the path from the leaf to the merkle root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ControlBlock {
+    pub leaf_version: u8,
+    pub output_key_parity: bool,
+    pub internal_key: [u8; 32],
+    pub merkle_path: Vec<[u8; 32]>,
+}
+
+impl ControlBlock {
+    /// Parses a control block
+    ///
+    /// The first byte is the leaf version with the output key parity in its lowest bit,
+    /// followed by the x-only internal key and the merkle path.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the length is not `33 + 32 * m` for a path of at most 128
+    /// hashes, or the internal key is not a valid x-only key
+    pub fn parse(bytes: &[u8]) -> Result<Self, TaprootError> {
+        let length = bytes.len();
+        if length < 33
+            || !(length - 33).is_multiple_of(32)
+            || (length - 33) / 32 > MAX_MERKLE_PATH_LENGTH
+        {
+            return Err(TaprootError::InvalidControlBlockLength(length));
+        }
+
+        let mut internal_key = [0u8; 32];
+        internal_key.copy_from_slice(&bytes[1..33]);
+        if !is_valid_xonly(&internal_key) {
+            return Err(TaprootError::InvalidInternalKey);
+        }
+
+        let merkle_path = bytes[33..]
+            .chunks_exact(32)
+            .map(|hash| {
+                let mut node = [0u8; 32];
+                node.copy_from_slice(hash);
+                node
+            })
+            .collect();
+
+        Ok(Self {
+            leaf_version: bytes[0] & 0xfe,
+            output_key_parity: bytes[0] & 1 == 1,
+            internal_key,
+            merkle_path,
+        })
+    }
+
+    /// Returns the size of the serialized control block, `33 + 32 * m`
+    pub fn size(&self) -> usize {
+        33 + 32 * self.merkle_path.len()
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.size());
+        bytes.push(self.leaf_version | u8::from(self.output_key_parity));
+        bytes.extend_from_slice(&self.internal_key);
+        for node in &self.merkle_path {
+            bytes.extend_from_slice(node);
+        }
+        bytes
+    }
+
+    /// Returns whether the control block proves the script is a leaf of the output key
+    ///
+    /// The leaf hash is folded up the merkle path into a root, and tweaking the internal
+    /// key with that root has to give the output key with the parity the block claims.
+    pub fn verify_script(&self, output_key: &[u8; 32], script: &[u8]) -> bool {
+        let merkle_root = self
+            .merkle_path
+            .iter()
+            .fold(tap_leaf_hash(self.leaf_version, script), |node, sibling| {
+                tap_branch_hash(&node, sibling)
+            });
+
+        let Ok(internal_key) =
+            Secp256k1Point::lift_x(&BigUint::from_bytes_be(&self.internal_key), false)
+        else {
+            return false;
+        };
+        tweak_public_key(&internal_key, Some(&merkle_root))
+            .is_ok_and(|tweaked| tweaked == (*output_key, self.output_key_parity))
+    }
+}
+
+/// Returns the BIP341 leaf hash, `hash_TapLeaf(leaf_version || compact_size(script) || script)`
+pub fn tap_leaf_hash(leaf_version: u8, script: &[u8]) -> [u8; 32] {
+    let mut data = vec![leaf_version];
+    data.extend(encode_varint(script.len() as u64));
+    data.extend_from_slice(script);
+    TAP_LEAF.hash(&data)
+}
+
+/// Returns the BIP341 branch hash of two children
+///
+/// The children are sorted before hashing, so a control block doesn't need to say which
+/// side each hash on its path is on.
+pub fn tap_branch_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(left);
+    data[32..].copy_from_slice(right);
+    TAP_BRANCH.hash(&data)
+}
+
+/// A tree of scripts an output key can commit to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TapTree {
+    Leaf { leaf_version: u8, script: Vec<u8> },
+    Branch(Box<TapTree>, Box<TapTree>),
+}
+
+impl TapTree {
+    /// Creates a tapscript leaf
+    pub fn leaf(script: Vec<u8>) -> Self {
+        Self::Leaf { leaf_version: TAPSCRIPT_LEAF_VERSION, script }
+    }
+
+    pub fn branch(left: TapTree, right: TapTree) -> Self {
+        Self::Branch(Box::new(left), Box::new(right))
+    }
+
+    /// Returns the hash of the tree's root, the merkle root the output key commits to
+    pub fn hash(&self) -> [u8; 32] {
+        match self {
+            Self::Leaf { leaf_version, script } => tap_leaf_hash(*leaf_version, script),
+            Self::Branch(left, right) => tap_branch_hash(&left.hash(), &right.hash()),
+        }
+    }
+
+    /// Returns the root hash along with every leaf and its path up to the root
+    fn leaves(&self) -> ([u8; 32], Vec<TapLeaf>) {
+        match self {
+            Self::Leaf { leaf_version, script } => {
+                let leaf = TapLeaf {
+                    leaf_version: *leaf_version,
+                    script: script.clone(),
+                    merkle_path: Vec::new(),
+                };
+                (tap_leaf_hash(*leaf_version, script), vec![leaf])
+            }
+            Self::Branch(left, right) => {
+                let (left_hash, mut left_leaves) = left.leaves();
+                let (right_hash, mut right_leaves) = right.leaves();
+                for leaf in &mut left_leaves {
+                    leaf.merkle_path.push(right_hash);
+                }
+                for leaf in &mut right_leaves {
+                    leaf.merkle_path.push(left_hash);
+                }
+                left_leaves.extend(right_leaves);
+                (tap_branch_hash(&left_hash, &right_hash), left_leaves)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TapLeaf {
+    leaf_version: u8,
+    script: Vec<u8>,
+    merkle_path: Vec<[u8; 32]>,
+}
+
+/// Everything a wallet keeps to spend a Taproot output by either path
+///
+/// The key path signs for the output key, any script path reveals a leaf with its
+/// control block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaprootSpendInfo {
+    internal_key: [u8; 32],
+    merkle_root: Option<[u8; 32]>,
+    output_key: [u8; 32],
+    output_key_parity: bool,
+    leaves: Vec<TapLeaf>,
+}
+
+impl TaprootSpendInfo {
+    /// Tweaks the internal key with the tree's root, a key path only output without one
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a leaf is more than `MAX_MERKLE_PATH_LENGTH` levels deep or
+    /// the key can't be tweaked, see `tweak_public_key`
+    pub fn new(
+        internal_key: &Secp256k1Point,
+        tree: Option<&TapTree>,
+    ) -> Result<Self, TaprootError> {
+        let (merkle_root, leaves) = match tree.map(TapTree::leaves) {
+            Some((root, leaves)) => (Some(root), leaves),
+            None => (None, Vec::new()),
+        };
+        if leaves.iter().any(|leaf| leaf.merkle_path.len() > MAX_MERKLE_PATH_LENGTH) {
+            return Err(TaprootError::TreeTooDeep);
+        }
+
+        let (output_key, output_key_parity) =
+            tweak_public_key(internal_key, merkle_root.as_ref())?;
+        let mut internal_x = [0u8; 32];
+        internal_x.copy_from_slice(&internal_key.to_sec(true)[1..]);
+
+        Ok(Self {
+            internal_key: internal_x,
+            merkle_root,
+            output_key,
+            output_key_parity,
+            leaves,
+        })
+    }
+
+    pub fn internal_key(&self) -> [u8; 32] {
+        self.internal_key
+    }
+
+    /// Returns the x-only output key, the witness program of the output
+    pub fn output_key(&self) -> [u8; 32] {
+        self.output_key
+    }
+
+    pub fn output_key_parity(&self) -> bool {
+        self.output_key_parity
+    }
+
+    /// Returns the root of the script tree, `None` for a key path only output
+    pub fn merkle_root(&self) -> Option<[u8; 32]> {
+        self.merkle_root
+    }
+
+    /// Returns the control block to spend the leaf, or `None` if it is not in the tree
+    pub fn control_block(&self, leaf_version: u8, script: &[u8]) -> Option<ControlBlock> {
+        let leaf = self
+            .leaves
+            .iter()
+            .find(|leaf| leaf.leaf_version == leaf_version && leaf.script == script)?;
+
+        Some(ControlBlock {
+            leaf_version,
+            output_key_parity: self.output_key_parity,
+            internal_key: self.internal_key,
+            merkle_path: leaf.merkle_path.clone(),
+        })
+    }
+}
+
+/// Tweaks an internal key into a BIP341 output key
+///
+/// The internal key is taken with an even y, as its x-only form stands for, and the
+/// output key is `P + hash_TapTweak(P.x || merkle_root) * G`. Without a merkle root
+/// only the key path can spend the output. Returns the x-only output key and whether
+/// its y is odd, the parity a control block carries.
+///
+/// # Errors
+///
+/// Returns an error if the internal key is the identity, the tweak is not below the
+/// group order or the output key is the identity
+pub fn tweak_public_key(
+    internal_key: &Secp256k1Point,
+    merkle_root: Option<&[u8; 32]>,
+) -> Result<([u8; 32], bool), TaprootError> {
+    if internal_key.is_identity() {
+        return Err(TaprootError::InvalidInternalKey);
+    }
+    let mut internal_x = [0u8; 32];
+    internal_x.copy_from_slice(&internal_key.to_sec(true)[1..]);
+    let internal_key = Secp256k1Point::lift_x(&BigUint::from_bytes_be(&internal_x), false)
+        .map_err(|_| TaprootError::InvalidInternalKey)?;
+
+    let mut data = internal_x.to_vec();
+    if let Some(merkle_root) = merkle_root {
+        data.extend_from_slice(merkle_root);
+    }
+    let tweak = BigUint::from_bytes_be(&TAP_TWEAK.hash(&data));
+    if tweak >= Secp256k1Point::order() {
+        return Err(TaprootError::InvalidTweak);
+    }
+
+    let output_key = internal_key + Secp256k1Point::mul_generator(&tweak);
+    if output_key.is_identity() {
+        return Err(TaprootError::InvalidTweak);
+    }
+    let sec = output_key.to_sec(true);
+    let mut output_x = [0u8; 32];
+    output_x.copy_from_slice(&sec[1..]);
+    Ok((output_x, sec[0] == 0x03))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{elliptic_curve::secp256k1::Secp256k1Point, helpers::bytes::ToBytesBigEndian};
+
+    fn generator_x() -> [u8; 32] {
+        Secp256k1Point::g().to_sec(true)[1..].try_into().unwrap()
+    }
+
+    #[test]
+    fn test_control_block_round_trip() {
+        let control_block = ControlBlock {
+            leaf_version: TAPSCRIPT_LEAF_VERSION,
+            output_key_parity: true,
+            internal_key: generator_x(),
+            merkle_path: vec![[0x11; 32], [0x22; 32]],
+        };
+
+        let bytes = control_block.serialize();
+        assert_eq!(bytes.len(), 97);
+        assert_eq!(control_block.size(), 97);
+        assert_eq!(bytes[0], 0xc1);
+        assert_eq!(ControlBlock::parse(&bytes), Ok(control_block));
+
+        // Script path spend of a tree with a single leaf, which has no merkle path
+        let parsed = ControlBlock::parse(&bytes[..33]).unwrap();
+        assert!(parsed.merkle_path.is_empty());
+        assert_eq!(parsed.serialize(), bytes[..33]);
+    }
+
+    #[test]
+    fn test_control_block_parse_errors() {
+        let mut bytes = vec![TAPSCRIPT_LEAF_VERSION];
+        bytes.extend_from_slice(&generator_x());
+        bytes.extend_from_slice(&[0x11; 32 * MAX_MERKLE_PATH_LENGTH]);
+        assert!(ControlBlock::parse(&bytes).is_ok());
+
+        for length in [0, 32, 34, 64, 96, bytes.len() - 1] {
+            assert_eq!(
+                ControlBlock::parse(&bytes[..length]),
+                Err(TaprootError::InvalidControlBlockLength(length))
+            );
+        }
+        bytes.extend_from_slice(&[0x11; 32]);
+        assert_eq!(
+            ControlBlock::parse(&bytes),
+            Err(TaprootError::InvalidControlBlockLength(bytes.len()))
+        );
+
+        // 5^3 + 7 is not a square modulo p
+        let mut bytes = [0u8; 33];
+        bytes[32] = 5;
+        assert_eq!(ControlBlock::parse(&bytes), Err(TaprootError::InvalidInternalKey));
+    }
+
+    #[test]
+    fn test_tweak_public_key() {
+        // BIP341 wallet test vectors, the first key path only and the second with a tree
+        let internal_key = Secp256k1Point::from_hex(
+            "02d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d",
+        )
+        .unwrap();
+        let (output_key, _) = tweak_public_key(&internal_key, None).unwrap();
+        assert_eq!(
+            crate::helpers::hex::encode(&output_key),
+            "53a1f6e454df1aa2776a2814a721372d6258050de330b3c6d10ee8f4e0dda343"
+        );
+
+        // Only x matters, the odd y key tweaks the same
+        let odd_key = Secp256k1Point::from_hex(
+            "03d6889cb081036e0faefa3a35157ad71086b123b2b144b649798b494c300a961d",
+        )
+        .unwrap();
+        assert_eq!(tweak_public_key(&odd_key, None).unwrap().0, output_key);
+
+        let internal_key = Secp256k1Point::from_hex(
+            "02187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27",
+        )
+        .unwrap();
+        let merkle_root: [u8; 32] = "5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21"
+            .to_bytes_be()
+            .try_into()
+            .unwrap();
+        let (output_key, parity) = tweak_public_key(&internal_key, Some(&merkle_root)).unwrap();
+        assert_eq!(
+            crate::helpers::hex::encode(&output_key),
+            "147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3"
+        );
+        assert!(parity);
+    }
+
+    #[test]
+    fn test_spend_info_single_leaf() {
+        // Second BIP341 wallet test vector
+        let internal_key = Secp256k1Point::from_hex(
+            "02187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27",
+        )
+        .unwrap();
+        let script = "20d85a959b0290bf19bb89ed43c916be835475d013da4b362117393e25a48229b8ac"
+            .to_bytes_be();
+        let tree = TapTree::leaf(script.clone());
+
+        let spend_info = TaprootSpendInfo::new(&internal_key, Some(&tree)).unwrap();
+        assert_eq!(
+            spend_info.merkle_root().unwrap().to_vec(),
+            "5b75adecf53548f3ec6ad7d78383bf84cc57b55a3127c72b9a2481752dd88b21".to_bytes_be()
+        );
+        assert_eq!(
+            spend_info.output_key().to_vec(),
+            "147c9c57132f6e7ecddba9800bb0c4449251c92a1e60371ee77557b6620f3ea3".to_bytes_be()
+        );
+
+        let control_block = spend_info.control_block(TAPSCRIPT_LEAF_VERSION, &script).unwrap();
+        assert_eq!(
+            control_block.serialize(),
+            "c1187791b6f712a8ea41c8ecdd0ee77fab3e85263b37e1ec18a3651926b3a6cf27".to_bytes_be()
+        );
+        assert!(control_block.verify_script(&spend_info.output_key(), &script));
+        assert!(spend_info.control_block(TAPSCRIPT_LEAF_VERSION, &[0x51]).is_none());
+
+        // Key path only
+        let key_only = TaprootSpendInfo::new(&internal_key, None).unwrap();
+        assert_eq!(key_only.merkle_root(), None);
+        assert!(key_only.control_block(TAPSCRIPT_LEAF_VERSION, &script).is_none());
+    }
+
+    #[test]
+    fn test_spend_info_two_leaves() {
+        let internal_key = Secp256k1Point::mul_generator(&BigUint::from(12345u32));
+        let scripts = [vec![0x51], vec![0x52, 0x87]];
+        let tree = TapTree::branch(
+            TapTree::leaf(scripts[0].clone()),
+            TapTree::leaf(scripts[1].clone()),
+        );
+
+        let spend_info = TaprootSpendInfo::new(&internal_key, Some(&tree)).unwrap();
+        assert_eq!(spend_info.merkle_root(), Some(tree.hash()));
+        assert_eq!(
+            (spend_info.output_key(), spend_info.output_key_parity()),
+            tweak_public_key(&internal_key, Some(&tree.hash())).unwrap()
+        );
+
+        for (i, script) in scripts.iter().enumerate() {
+            let control_block = spend_info.control_block(TAPSCRIPT_LEAF_VERSION, script).unwrap();
+            assert_eq!(
+                control_block.merkle_path,
+                [tap_leaf_hash(TAPSCRIPT_LEAF_VERSION, &scripts[1 - i])]
+            );
+            assert_eq!(ControlBlock::parse(&control_block.serialize()), Ok(control_block.clone()));
+            assert!(control_block.verify_script(&spend_info.output_key(), script));
+            assert!(!control_block.verify_script(&spend_info.output_key(), &scripts[1 - i]));
+
+            let mut flipped = control_block;
+            flipped.output_key_parity = !flipped.output_key_parity;
+            assert!(!flipped.verify_script(&spend_info.output_key(), script));
+        }
+
+        // Swapping the branches gives the same root, children are sorted
+        let swapped = TapTree::branch(
+            TapTree::leaf(scripts[1].clone()),
+            TapTree::leaf(scripts[0].clone()),
+        );
+        assert_eq!(swapped.hash(), tree.hash());
+    }
+}
@@ -0,0 +1,73 @@
+//! Known answer test vectors, embedded as JSON and CSV
+//!
+//! New vectors can be added to the files without touching the tests that iterate
+//! over them.
+use serde::Deserialize;
+
+/// A multiple of the secp256k1 generator, coordinates in decimal
+#[derive(Debug, Deserialize)]
+pub struct PointVector {
+    pub scalar: String,
+    pub x: String,
+    pub y: String,
+}
+
+/// An ECDSA verification case on secp256k1, values in hex
+#[derive(Debug, Deserialize)]
+pub struct EcdsaVector {
+    pub public_x: String,
+    pub public_y: String,
+    pub z: String,
+    pub r: String,
+    pub s: String,
+    pub valid: bool,
+}
+
+/// A row of the BIP340 test vectors, values in hex
+///
+/// The secret key is only given for the vectors that come with a signing test, the
+/// message can be empty. There is no BIP340 signing here, so `aux_rand` is not kept.
+#[derive(Debug)]
+pub struct SchnorrVector {
+    pub index: usize,
+    pub secret_key: String,
+    pub public_key: String,
+    pub message: String,
+    pub signature: String,
+    pub valid: bool,
+    pub comment: String,
+}
+
+pub fn secp256k1_points() -> Vec<PointVector> {
+    serde_json::from_str(include_str!("secp256k1.json")).unwrap()
+}
+
+pub fn ecdsa() -> Vec<EcdsaVector> {
+    serde_json::from_str(include_str!("ecdsa.json")).unwrap()
+}
+
+/// BIP340 verification cases, `test-vectors.csv` from the BIP as published
+///
+/// # Panics
+///
+/// Panics if a row doesn't have the BIP's eight columns
+pub fn bip340() -> Vec<SchnorrVector> {
+    include_str!("bip340_test_vectors.csv")
+        .lines()
+        .skip(1)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let columns: Vec<&str> = line.trim_end().splitn(8, ',').collect();
+            assert_eq!(columns.len(), 8, "{line}");
+            SchnorrVector {
+                index: columns[0].parse().unwrap(),
+                secret_key: columns[1].to_string(),
+                public_key: columns[2].to_string(),
+                message: columns[4].to_string(),
+                signature: columns[5].to_string(),
+                valid: columns[6] == "TRUE",
+                comment: columns[7].to_string(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,36 @@
+#![allow(unused)]
+use crate::helpers::bytes::encode_varint;
+
+/// Returns the weight a witness stack adds to its transaction
+///
+/// Witness data counts one weight unit per byte, against four for everything else. The
+/// serialized stack is the number of items followed by each item with its length, both
+/// as varints, so this is also its size in bytes.
+pub fn witness_weight(witness: &[Vec<u8>]) -> usize {
+    let varint_size = |value: usize| encode_varint(value as u64).len();
+
+    witness.iter().fold(varint_size(witness.len()), |weight, item| {
+        weight + varint_size(item.len()) + item.len()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_witness_weight() {
+        // P2WPKH, a 72 byte DER signature and a compressed public key
+        let p2wpkh = vec![vec![0x30; 72], vec![0x02; 33]];
+        assert_eq!(witness_weight(&p2wpkh), 1 + 1 + 72 + 1 + 33);
+
+        // A Taproot key path spend with the default sighash
+        assert_eq!(witness_weight(&[vec![0; 64]]), 66);
+
+        // Items from 253 bytes on take a 3 byte length, empty items still take one byte
+        let custom = vec![vec![], vec![0x51; 253], vec![0x01]];
+        assert_eq!(witness_weight(&custom), 1 + 1 + (3 + 253) + (1 + 1));
+
+        assert_eq!(witness_weight(&[]), 1);
+    }
+}